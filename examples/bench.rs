@@ -0,0 +1,88 @@
+//! Measures throughput and peak heap usage of `parse` on a generated document.
+//!
+//! Run with `cargo run --release --example bench -- [size in MB]`.
+
+use json_parser::parser::parse;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    env,
+    fmt::Write,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+struct PeakAllocator;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let current = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK.fetch_max(current, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: PeakAllocator = PeakAllocator;
+
+fn generate(target_bytes: usize) -> String {
+    let mut json = String::from("[");
+    let mut idx = 0;
+    while json.len() < target_bytes {
+        if idx > 0 {
+            json.push(',');
+        }
+        write!(
+            json,
+            r#"{{"id":{},"name":"user-{}","score":{}.{},"tags":["a\tb","grüße","ü"],"active":{},"parent":null}}"#,
+            idx,
+            idx,
+            idx % 1000,
+            idx % 97,
+            idx % 2 == 0
+        )
+        .unwrap();
+        idx += 1;
+    }
+    json.push(']');
+    json
+}
+
+fn main() {
+    let megabytes = env::args()
+        .nth(1)
+        .and_then(|arg| arg.parse::<usize>().ok())
+        .unwrap_or(100);
+    let json = generate(megabytes * 1024 * 1024);
+    let baseline = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+
+    let start = Instant::now();
+    let value = parse(&json).expect("generated JSON is valid");
+    let elapsed = start.elapsed();
+    let peak = PEAK.load(Ordering::Relaxed) - baseline;
+    let retained = CURRENT.load(Ordering::Relaxed) - baseline;
+    drop(value);
+
+    let input_mb = json.len() as f64 / (1024.0 * 1024.0);
+    println!("input:      {:.1} MB", input_mb);
+    println!("time:       {:.2?}", elapsed);
+    println!("throughput: {:.1} MB/s", input_mb / elapsed.as_secs_f64());
+    println!(
+        "peak heap:  {:.1} MB ({:.2}x input)",
+        peak as f64 / (1024.0 * 1024.0),
+        peak as f64 / json.len() as f64
+    );
+    println!("value tree: {:.1} MB", retained as f64 / (1024.0 * 1024.0));
+    println!(
+        "overhead:   {:.1} MB (peak heap minus value tree)",
+        (peak - retained) as f64 / (1024.0 * 1024.0)
+    );
+}
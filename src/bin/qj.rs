@@ -1,29 +1,56 @@
 use clap::Parser;
-use json_parser::parser::parse;
+use json_parser::parser::{parse_reader, JsonWriter};
+use std::io::{BufWriter, IsTerminal, Write};
 use std::time::Instant;
-use std::{fs, process};
+use std::{env, fs, fs::File, io, process};
 
 #[derive(Parser)]
 #[command(author, version, about)]
 struct Cli {
-    json_file: String,
+    /// File to read, standard input if omitted or `-`
+    json_file: Option<String>,
 }
 
 fn main() {
     let start = Instant::now();
     let cli = Cli::parse();
-    let file = fs::read_to_string(cli.json_file).unwrap_or_else(|err| {
-        eprintln!("Error reading file: {}", err);
-        process::exit(1)
-    });
-    let value = parse(&file).unwrap_or_else(|err| {
-        eprintln!("{}", err);
+    let parsed = match cli.json_file.as_deref() {
+        None | Some("-") => parse_reader(io::stdin().lock()),
+        Some(path) => {
+            let file = File::open(path).unwrap_or_else(|err| {
+                eprintln!("Error reading file: {}", err);
+                process::exit(1)
+            });
+            parse_reader(file)
+        }
+    };
+    let value = parsed.unwrap_or_else(|err| {
+        // The input was streamed, so only a file can be read again to show
+        // the offending line.
+        match cli.json_file.as_deref().filter(|path| *path != "-") {
+            Some(path) => {
+                let source = fs::read(path).unwrap_or_default();
+                let source = String::from_utf8_lossy(&source);
+                let color = io::stderr().is_terminal() && env::var_os("NO_COLOR").is_none();
+                eprintln!("{}", err.diagnostic(&source).color(color));
+            }
+            None => eprintln!("{}", err),
+        }
         process::exit(1);
     });
     println!("Parsed in {:?}", start.elapsed());
     let start_stringify = Instant::now();
-    let stringified = value.stringified().create();
-    println!("{}", stringified);
+    // Streamed to stdout so that huge values are not held twice in memory.
+    let mut writer = JsonWriter::new(BufWriter::new(io::stdout().lock()));
+    let written = writer.value(&value).and_then(|_| {
+        let mut stdout = writer.finish()?;
+        writeln!(stdout)?;
+        Ok(stdout.flush()?)
+    });
+    if let Err(err) = written {
+        eprintln!("Error writing output: {}", err);
+        process::exit(1);
+    }
     println!("Stringified in {:?}", start_stringify.elapsed());
     println!("Took {:?}", start.elapsed());
 }
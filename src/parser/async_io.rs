@@ -0,0 +1,221 @@
+use super::{
+    incremental::IncrementalParser, ndjson::Lines, options::ParseOptions, parse_error::ParseError,
+    source::READ_CHUNK_SIZE, value::Value,
+};
+use futures_core::Stream;
+use std::{
+    pin::{pin, Pin},
+    task::{ready, Context, Poll},
+};
+use tokio::io::{AsyncBufRead, AsyncRead, AsyncReadExt, BufReader};
+
+/// Like [`parse_reader`](super::parse_reader), but reads from `reader`
+/// asynchronously, so that waiting for input does not block the thread.
+pub async fn parse_async(reader: impl AsyncRead) -> Result<Value, ParseError> {
+    parse_async_with(reader, ParseOptions::default()).await
+}
+
+/// Like [`parse_async`], but configured by `options`.
+pub async fn parse_async_with(
+    reader: impl AsyncRead,
+    options: impl Into<ParseOptions>,
+) -> Result<Value, ParseError> {
+    let mut reader = pin!(reader);
+    let mut parser = IncrementalParser::document(options.into());
+    let mut buffer = vec![0; READ_CHUNK_SIZE];
+    let mut value = None;
+    loop {
+        let length = reader.read(&mut buffer).await.map_err(ParseError::Io)?;
+        if length == 0 {
+            parser.finish();
+        } else {
+            parser.feed(&buffer[..length]);
+        }
+        // Reading goes on after the value to reject trailing content.
+        while let Some(result) = parser.next_value() {
+            value = Some(result?);
+        }
+        if length == 0 {
+            return Ok(value.expect("a value was parsed"));
+        }
+    }
+}
+
+/// Asynchronous counterpart of [`NdjsonReader`](super::NdjsonReader): a
+/// [`Stream`] of the values of newline-delimited JSON, one per line, each
+/// buffered only up to the input size limit.
+pub struct AsyncNdjsonReader<R: AsyncBufRead> {
+    reader: R,
+    lines: Lines,
+}
+
+impl<R: AsyncBufRead> AsyncNdjsonReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            lines: Lines::default(),
+        }
+    }
+
+    /// Parse every line according to `options`.
+    pub fn options(mut self, options: impl Into<ParseOptions>) -> Self {
+        self.lines.options = options.into();
+        self
+    }
+
+    /// Skip malformed lines instead of stopping at them. Errors reading the
+    /// input still end the stream.
+    pub fn skip_invalid(mut self, skip_invalid: bool) -> Self {
+        self.lines.skip_invalid = skip_invalid;
+        self
+    }
+}
+
+impl<R: AsyncRead> AsyncNdjsonReader<BufReader<R>> {
+    /// Reads from an unbuffered `reader`.
+    pub fn from_reader(reader: R) -> Self {
+        Self::new(BufReader::new(reader))
+    }
+}
+
+impl<R: AsyncBufRead + Unpin> Stream for AsyncNdjsonReader<R> {
+    type Item = Result<Value, ParseError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        while !this.lines.is_done {
+            let available = match ready!(Pin::new(&mut this.reader).poll_fill_buf(cx)) {
+                Ok(available) => available,
+                Err(error) => return Poll::Ready(Some(this.lines.fail(error))),
+            };
+            let (length, result) = this.lines.read(available);
+            Pin::new(&mut this.reader).consume(length);
+            if result.is_some() {
+                return Poll::Ready(result);
+            }
+        }
+        Poll::Ready(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse, Dialect, ErrorKind};
+    use std::future::poll_fn;
+    use tokio::io::{duplex, AsyncWriteExt};
+
+    async fn next<S: Stream + Unpin>(stream: &mut S) -> Option<S::Item> {
+        poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx)).await
+    }
+
+    #[tokio::test]
+    async fn parse_from_pipe() {
+        let (mut writer, reader) = duplex(8);
+        let json = r#"{"name": "ü 🎉", "values": [1, -2.5e3, true, null]}"#;
+        let write = async {
+            for chunk in json.as_bytes().chunks(5) {
+                writer.write_all(chunk).await.unwrap();
+            }
+            drop(writer);
+        };
+        let (_, value) = tokio::join!(write, parse_async(reader));
+        assert_eq!(value.unwrap(), parse(json).unwrap());
+    }
+
+    #[tokio::test]
+    async fn large_body_from_pipe() {
+        let (mut writer, reader) = duplex(64);
+        let text = "abcdefgh".repeat(1 << 19);
+        let json = format!(r#"{{"text": "{text}", "n": [1, 2]}}"#);
+        let write = async {
+            writer.write_all(json.as_bytes()).await.unwrap();
+            drop(writer);
+        };
+        let (_, value) = tokio::join!(write, parse_async(reader));
+        assert_eq!(value.unwrap(), parse(&json).unwrap());
+    }
+
+    #[tokio::test]
+    async fn parse_errors() {
+        let kind = |json: &'static str| async move {
+            parse_async(json.as_bytes()).await.unwrap_err().kind()
+        };
+        assert_eq!(kind("[1, 2").await, ErrorKind::UnexpectedEndOfFile);
+        assert_eq!(kind("1 2").await, ErrorKind::TrailingContent);
+        assert_eq!(kind("").await, ErrorKind::UnexpectedEndOfFile);
+        assert_eq!(
+            kind("[\"\u{1}\"]").await,
+            ErrorKind::ControlCharacterInString
+        );
+        let error = parse_async_with("[1,]".as_bytes(), ParseOptions::default())
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::UnexpectedToken);
+        let value = parse_async_with("[1,]".as_bytes(), Dialect::Jsonc).await;
+        assert_eq!(value.unwrap(), parse("[1]").unwrap());
+    }
+
+    #[tokio::test]
+    async fn ndjson_from_pipe() {
+        let (mut writer, reader) = duplex(4);
+        let input = "{\"a\": 1}\n\n[1,\n\"text\"\r\n2";
+        let write = async {
+            writer.write_all(input.as_bytes()).await.unwrap();
+            drop(writer);
+        };
+        let read = async {
+            let mut stream = AsyncNdjsonReader::from_reader(reader).skip_invalid(true);
+            let mut values = Vec::new();
+            while let Some(value) = next(&mut stream).await {
+                values.push(value.unwrap());
+            }
+            values
+        };
+        let (_, values) = tokio::join!(write, read);
+        let expected: Vec<_> = [r#"{"a": 1}"#, "\"text\"", "2"]
+            .into_iter()
+            .map(|json| parse(json).unwrap())
+            .collect();
+        assert_eq!(values, expected);
+    }
+
+    #[tokio::test]
+    async fn ndjson_line_too_long() {
+        let options = ParseOptions::new().max_input_size(8);
+        let (mut writer, reader) = duplex(4);
+        let write = async {
+            writer.write_all(b"[1]\n[1, 2, 3, 4]\n[2]").await.unwrap();
+            drop(writer);
+        };
+        let read = async {
+            let mut stream = AsyncNdjsonReader::from_reader(reader)
+                .options(options.clone())
+                .skip_invalid(true);
+            let mut values = Vec::new();
+            while let Some(value) = next(&mut stream).await {
+                values.push(value.unwrap());
+            }
+            values
+        };
+        let (_, values) = tokio::join!(write, read);
+        assert_eq!(values, vec![parse("[1]").unwrap(), parse("[2]").unwrap()]);
+        let mut stream = AsyncNdjsonReader::from_reader(tokio::io::repeat(b' ')).options(options);
+        let error = next(&mut stream).await.unwrap().unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InputSizeLimitExceeded);
+        assert!(next(&mut stream).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn ndjson_error() {
+        let input = "1\n\n  {\"a\": nul}\n3\n";
+        let mut stream = AsyncNdjsonReader::from_reader(input.as_bytes());
+        assert_eq!(
+            next(&mut stream).await.unwrap().unwrap(),
+            parse("1").unwrap()
+        );
+        let error = next(&mut stream).await.unwrap().unwrap_err();
+        assert_eq!(error.location().map(|location| location.line), Some(3));
+        assert!(next(&mut stream).await.is_none());
+    }
+}
@@ -0,0 +1,632 @@
+use super::{
+    options::ParseOptions,
+    parse_error::{ParseError, ParseErrorArgs, TokenKind},
+    parser::{
+        check_duplicate, check_members, ends_container, next_token, property_key, seen_keys,
+        unexpected_value, unwrap_token, SeenKeys,
+    },
+    tokenizer::{Token, TokenType, Tokenizer},
+    value::Value,
+};
+use std::fmt::{self, Display};
+
+/// A JSON text as a concrete syntax tree, keeping the raw text of every token
+/// along with the whitespace and comments in between. Printing it reproduces
+/// the input byte for byte, apart from the parts changed by
+/// [`set`](Document::set), [`insert`](Document::insert) and
+/// [`remove`](Document::remove).
+///
+/// Locations are given as JSON Pointers (RFC 6901) such as `/servers/0/port`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    leading: String,
+    root: Node,
+    trailing: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    /// A literal, number or string as written in the source.
+    Scalar(String),
+    Object(Container),
+    Array(Container),
+}
+
+/// Members of an object or elements of an array.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Container {
+    items: Vec<Item>,
+    /// Trivia before the closing bracket that follows a trailing comma, or
+    /// everything between the brackets of an empty container.
+    end: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Item {
+    /// Trivia after the opening bracket or the previous comma.
+    leading: String,
+    key: Option<Key>,
+    value: Node,
+    /// Trivia between the value and the comma or closing bracket.
+    trailing: String,
+    comma: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Key {
+    raw: String,
+    name: String,
+    before_colon: String,
+    after_colon: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum EditError {
+    InvalidPointer(String),
+    PathNotFound(String),
+    NotAnObject(String),
+    KeyExists(String),
+}
+
+impl Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPointer(pointer) => {
+                write!(f, "`{}` is not a valid JSON Pointer", pointer)
+            }
+            Self::PathNotFound(pointer) => write!(f, "No value at `{}`", pointer),
+            Self::NotAnObject(pointer) => write!(f, "Value at `{}` is not an object", pointer),
+            Self::KeyExists(key) => write!(f, "Key `{}` already exists", key),
+        }
+    }
+}
+
+impl std::error::Error for EditError {}
+
+impl Document {
+    pub fn parse(input: &str, options: impl Into<ParseOptions>) -> Result<Self, ParseError> {
+        let mut builder = CstBuilder {
+            input,
+            tokenizer: Tokenizer::with_options(input, options.into()),
+            end: 0,
+            depth: 0,
+            values: 0,
+        };
+        let (leading, token) = builder.token()?;
+        let root = builder.node(token)?;
+        if let Some(token) = next_token(&mut builder.tokenizer)? {
+            return Err(ParseError::trailing_content(token));
+        }
+        Ok(Self {
+            leading,
+            root,
+            trailing: input[builder.end..].to_owned(),
+        })
+    }
+
+    pub fn root(&self) -> &Node {
+        &self.root
+    }
+
+    pub fn get(&self, pointer: &str) -> Option<&Node> {
+        let mut node = &self.root;
+        for segment in segments(pointer).ok()? {
+            let index = node.position(&segment)?;
+            node = &node.container()?.items[index].value;
+        }
+        Some(node)
+    }
+
+    /// Replaces the value at `pointer`, keeping the surrounding formatting.
+    pub fn set(&mut self, pointer: &str, value: &Value) -> Result<(), EditError> {
+        *self.node_mut(pointer)? = Node::from(value);
+        Ok(())
+    }
+
+    /// Adds `key` to the end of the object at `pointer`, imitating the layout
+    /// of the existing members.
+    pub fn insert(&mut self, pointer: &str, key: &str, value: &Value) -> Result<(), EditError> {
+        let Node::Object(object) = self.node_mut(pointer)? else {
+            return Err(EditError::NotAnObject(pointer.to_owned()));
+        };
+        if object.items.iter().any(|item| item.name() == Some(key)) {
+            return Err(EditError::KeyExists(key.to_owned()));
+        }
+        let mut item = Item::member(key, value);
+        match object.items.last_mut() {
+            None => item.trailing = std::mem::take(&mut object.end),
+            Some(last) => {
+                let indentation = match split_trivia(&last.leading).1 {
+                    "" => " ".to_owned(),
+                    indentation => indentation.to_owned(),
+                };
+                // Trivia between the last value and the closing bracket.
+                let after_last = if last.comma {
+                    &mut object.end
+                } else {
+                    &mut last.trailing
+                };
+                let (same_line, rest) = split_trivia(after_last);
+                item.leading = format!("{}{}", same_line, indentation);
+                let rest = rest.to_owned();
+                if last.comma {
+                    // Stick to trailing commas.
+                    item.comma = true;
+                    *after_last = rest;
+                } else {
+                    item.trailing = rest;
+                    after_last.clear();
+                    last.comma = true;
+                }
+            }
+        }
+        object.items.push(item);
+        Ok(())
+    }
+
+    /// Removes the member or element at `pointer`, together with comments on
+    /// its lines.
+    pub fn remove(&mut self, pointer: &str) -> Result<(), EditError> {
+        let mut segments = segments(pointer)?;
+        let not_found = || EditError::PathNotFound(pointer.to_owned());
+        let last = segments.pop().ok_or_else(not_found)?;
+        let parent_pointer = &pointer[..pointer.rfind('/').unwrap_or(0)];
+        let parent = self.node_mut(parent_pointer)?;
+        let index = parent.position(&last).ok_or_else(not_found)?;
+        let container = parent.container_mut().expect("positions lie in containers");
+        let removed = container.items.remove(index);
+        // Whatever follows the previous comma on its line, like a comment,
+        // belongs to the previous item. On a single line, the removed item's
+        // spacing is taken over instead.
+        let kept = split_trivia(&removed.leading).0;
+        let rejoin = |trivia: &str, single_line: &str| match split_trivia(trivia).1 {
+            "" => single_line.to_owned(),
+            rest => format!("{}{}", kept, rest),
+        };
+        if let Some(next) = container.items.get_mut(index) {
+            next.leading = rejoin(&next.leading, &removed.leading);
+        } else if removed.comma {
+            container.end = rejoin(&container.end, &container.end);
+        } else if let Some(previous) = container.items.last_mut() {
+            previous.comma = false;
+            previous.trailing = rejoin(&removed.trailing, &removed.trailing);
+        } else {
+            container.end = split_trivia(&removed.trailing).1.to_owned();
+        }
+        Ok(())
+    }
+
+    fn node_mut(&mut self, pointer: &str) -> Result<&mut Node, EditError> {
+        let mut node = &mut self.root;
+        for segment in segments(pointer)? {
+            let index = node
+                .position(&segment)
+                .ok_or_else(|| EditError::PathNotFound(pointer.to_owned()))?;
+            let container = node.container_mut().expect("positions lie in containers");
+            node = &mut container.items[index].value;
+        }
+        Ok(node)
+    }
+}
+
+impl Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{}", self.leading, self.root, self.trailing)
+    }
+}
+
+impl Node {
+    fn container(&self) -> Option<&Container> {
+        match self {
+            Node::Object(container) | Node::Array(container) => Some(container),
+            Node::Scalar(_) => None,
+        }
+    }
+
+    fn container_mut(&mut self) -> Option<&mut Container> {
+        match self {
+            Node::Object(container) | Node::Array(container) => Some(container),
+            Node::Scalar(_) => None,
+        }
+    }
+
+    /// Index of the item `segment` refers to. Of repeated keys, the last one
+    /// counts like in a parsed [`Value`].
+    fn position(&self, segment: &str) -> Option<usize> {
+        match self {
+            Node::Object(object) => object
+                .items
+                .iter()
+                .rposition(|item| item.name() == Some(segment)),
+            Node::Array(array) => segment
+                .parse::<usize>()
+                .ok()
+                .filter(|index| *index < array.items.len()),
+            Node::Scalar(_) => None,
+        }
+    }
+}
+
+impl Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Node::Scalar(raw) => write!(f, "{}", raw),
+            Node::Object(object) => write!(f, "{{{}}}", object),
+            Node::Array(array) => write!(f, "[{}]", array),
+        }
+    }
+}
+
+/// Lays out a new value on a single line.
+impl From<&Value> for Node {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::Object(object) => Node::Object(Container::from_items(
+                object.iter().map(|(key, value)| Item::member(key, value)),
+            )),
+            Value::Array(array) => Node::Array(Container::from_items(
+                array.iter().map(|value| Item::element(Node::from(value))),
+            )),
+            scalar => Node::Scalar(scalar.stringified().create()),
+        }
+    }
+}
+
+impl Container {
+    fn from_items(items: impl Iterator<Item = Item>) -> Self {
+        let mut items: Vec<Item> = items.collect();
+        let count = items.len();
+        for (index, item) in items.iter_mut().enumerate() {
+            if index > 0 {
+                item.leading = " ".to_owned();
+            }
+            item.comma = index + 1 < count;
+        }
+        Self {
+            items,
+            end: String::new(),
+        }
+    }
+}
+
+impl Display for Container {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for item in &self.items {
+            write!(f, "{}", item.leading)?;
+            if let Some(key) = &item.key {
+                write!(f, "{}{}:{}", key.raw, key.before_colon, key.after_colon)?;
+            }
+            write!(f, "{}{}", item.value, item.trailing)?;
+            if item.comma {
+                write!(f, ",")?;
+            }
+        }
+        write!(f, "{}", self.end)
+    }
+}
+
+impl Item {
+    fn element(value: Node) -> Self {
+        Self {
+            leading: String::new(),
+            key: None,
+            value,
+            trailing: String::new(),
+            comma: false,
+        }
+    }
+
+    fn member(key: &str, value: &Value) -> Self {
+        Self {
+            key: Some(Key {
+                raw: Value::String(key.to_owned()).stringified().create(),
+                name: key.to_owned(),
+                before_colon: String::new(),
+                after_colon: " ".to_owned(),
+            }),
+            ..Self::element(Node::from(value))
+        }
+    }
+
+    fn name(&self) -> Option<&str> {
+        self.key.as_ref().map(|key| key.name.as_str())
+    }
+}
+
+/// Splits trivia following a value at its first line break outside of a
+/// comment, into what stays on the value's line and the rest.
+fn split_trivia(trivia: &str) -> (&str, &str) {
+    let bytes = trivia.as_bytes();
+    let mut index = 0;
+    while index < bytes.len() {
+        match (bytes[index], bytes.get(index + 1)) {
+            (b'\r' | b'\n', _) => break,
+            (b'/', Some(b'*')) => {
+                index = trivia[index + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |end| index + end + 4);
+            }
+            _ => index += 1,
+        }
+    }
+    trivia.split_at(index)
+}
+
+/// Reference tokens of a JSON Pointer with `~1` and `~0` unescaped.
+fn segments(pointer: &str) -> Result<Vec<String>, EditError> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(tokens) = pointer.strip_prefix('/') else {
+        return Err(EditError::InvalidPointer(pointer.to_owned()));
+    };
+    Ok(tokens
+        .split('/')
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+/// Recursive-descent parser collecting the trivia between tokens.
+struct CstBuilder<'a> {
+    input: &'a str,
+    tokenizer: Tokenizer<'a>,
+    /// Byte offset right after the last token.
+    end: usize,
+    depth: usize,
+    values: usize,
+}
+
+impl<'a> CstBuilder<'a> {
+    /// The next token together with the trivia preceding it.
+    fn token(&mut self) -> Result<(String, Token<'a>), ParseError> {
+        let token = unwrap_token(&mut self.tokenizer)?;
+        let trivia = self.input[self.end..token.location.offset].to_owned();
+        self.end = token.location.offset + token.value.len();
+        Ok((trivia, token))
+    }
+
+    fn node(&mut self, token: Token<'a>) -> Result<Node, ParseError> {
+        self.values += 1;
+        if self.values > self.tokenizer.options().max_values {
+            return Err(ParseError::ValueLimitExceeded {
+                location: token.location,
+                limit: self.tokenizer.options().max_values,
+            });
+        }
+        let is_object = match token.token_type {
+            TokenType::Null
+            | TokenType::True
+            | TokenType::False
+            | TokenType::Number(_)
+            | TokenType::String(_) => return Ok(Node::Scalar(token.value.into_owned())),
+            TokenType::BeginObject => true,
+            TokenType::BeginArray => false,
+            _ => return Err(unexpected_value(token)),
+        };
+        let limit = self.tokenizer.options().max_depth;
+        if self.depth >= limit {
+            return Err(ParseError::DepthLimitExceeded {
+                location: token.location,
+                limit,
+            });
+        }
+        self.depth += 1;
+        let container = self.container(is_object)?;
+        self.depth -= 1;
+        Ok(if is_object {
+            Node::Object(container)
+        } else {
+            Node::Array(container)
+        })
+    }
+
+    fn container(&mut self, is_object: bool) -> Result<Container, ParseError> {
+        let (closing, expected) = if is_object {
+            (
+                TokenType::EndObject,
+                vec![TokenKind::EndObject, TokenKind::ValueSeparator],
+            )
+        } else {
+            (
+                TokenType::EndArray,
+                vec![TokenKind::EndArray, TokenKind::ValueSeparator],
+            )
+        };
+        let mut items: Vec<Item> = Vec::new();
+        let mut keys = seen_keys(&self.tokenizer);
+        loop {
+            let (leading, token) = self.token()?;
+            if token.token_type == closing && ends_container(&self.tokenizer, items.len()) {
+                return Ok(Container {
+                    items,
+                    end: leading,
+                });
+            }
+            check_members(&self.tokenizer, items.len() + 1, &token)?;
+            let (key, token) = if is_object {
+                let (key, token) = self.key(token, &mut keys)?;
+                (Some(key), token)
+            } else {
+                (None, token)
+            };
+            let value = self.node(token)?;
+            let (trailing, token) = self.token()?;
+            let comma = match token.token_type {
+                TokenType::ValueSeparator => true,
+                _ if token.token_type == closing => false,
+                _ => {
+                    return Err(ParseError::UnexpectedToken(ParseErrorArgs::new(
+                        token, expected,
+                    )))
+                }
+            };
+            items.push(Item {
+                leading,
+                key,
+                value,
+                trailing,
+                comma,
+            });
+            if !comma {
+                return Ok(Container {
+                    items,
+                    end: String::new(),
+                });
+            }
+        }
+    }
+
+    /// Reads a key and its colon, returning the first token of the value.
+    fn key(
+        &mut self,
+        token: Token<'a>,
+        keys: &mut SeenKeys,
+    ) -> Result<(Key, Token<'a>), ParseError> {
+        let (raw, location) = (token.value.to_string(), token.location);
+        let name = property_key(&self.tokenizer, token)?.into_owned();
+        check_duplicate(keys, &name, location)?;
+        let (before_colon, token) = self.token()?;
+        if token.token_type != TokenType::NameSeparator {
+            return Err(ParseError::UnexpectedToken(ParseErrorArgs::new(
+                token,
+                vec![TokenKind::NameSeparator],
+            )));
+        }
+        let (after_colon, token) = self.token()?;
+        let key = Key {
+            raw,
+            name,
+            before_colon,
+            after_colon,
+        };
+        Ok((key, token))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse, parse_with, Dialect, DuplicateKeyPolicy};
+    use test_case::test_case;
+
+    fn jsonc(input: &str) -> Document {
+        Document::parse(input, Dialect::Jsonc).unwrap()
+    }
+
+    #[test_case(" {\"a\" :1,\"b\":[ true , null ]}\n" ; "Odd spacing")]
+    #[test_case("{\r\n  \"grüße\": \"\\u00fc\",\r\n  \"n\": 1.50e+3\r\n}" ; "Escapes, numbers and CRLF")]
+    #[test_case("// settings\n{\n  /* tab */ \"tab\": 4, // spaces\n  \"list\": [1, 2,],\n}\n" ; "Comments and trailing commas")]
+    #[test_case("[ ]" ; "Empty container")]
+    #[test_case("\"text\"" ; "Scalar")]
+    fn round_trip(input: &str) {
+        assert_eq!(jsonc(input).to_string(), input);
+    }
+
+    #[test_case("[1, 2" ; "Unexpected end of file")]
+    #[test_case("[1 2]" ; "Missing comma")]
+    #[test_case(r#"{"a" 1}"# ; "Missing colon")]
+    #[test_case("[1] x" ; "Trailing content")]
+    #[test_case("[1,]" ; "Trailing comma")]
+    fn invalid_documents(input: &str) {
+        let error = Document::parse(input, ParseOptions::new()).unwrap_err();
+        assert_eq!(error.to_string(), parse(input).unwrap_err().to_string());
+    }
+
+    #[test_case(ParseOptions::new().duplicate_keys(DuplicateKeyPolicy::Error), r#"{"a":1,"a":2}"# ; "Duplicate key")]
+    #[test_case(ParseOptions::new().max_members(2), "[1,2,3]" ; "Members")]
+    #[test_case(ParseOptions::new().max_values(2), "[1,2,3]" ; "Values")]
+    fn limits(options: ParseOptions, input: &str) {
+        let error = Document::parse(input, options.clone()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            parse_with(input, options).unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn get() {
+        let document = jsonc(r#"{"a": [1, {"b/c": "x"}], "a": [true]}"#);
+        assert_eq!(document.get("/a/0").unwrap().to_string(), "true");
+        assert_eq!(document.get("").unwrap(), document.root());
+        let document = jsonc(r#"{"a": [1, {"b/c": "x"}]}"#);
+        assert_eq!(document.get("/a/1/b~1c").unwrap().to_string(), "\"x\"");
+        assert!(document.get("/a/2").is_none());
+        assert!(document.get("a").is_none());
+    }
+
+    #[test]
+    fn set() {
+        let mut document =
+            jsonc("{\n  // Port to listen on\n  \"port\": 80, // default\n  \"hosts\": []\n}");
+        let value = parse(r#"{"port": 8080, "hosts": ["a", {"b": null}]}"#).unwrap();
+        let Value::Object(object) = &value else {
+            panic!("Expected object");
+        };
+        document.set("/port", &object["port"]).unwrap();
+        document.set("/hosts", &object["hosts"]).unwrap();
+        assert_eq!(
+            document.to_string(),
+            "{\n  // Port to listen on\n  \"port\": 8080, // default\n  \"hosts\": [\"a\", {\"b\": null}]\n}"
+        );
+        assert_eq!(
+            parse_with(&document.to_string(), Dialect::Jsonc).unwrap(),
+            value
+        );
+    }
+
+    #[test_case("{\n  \"a\": 1\n}", "{\n  \"a\": 1,\n  \"b\": true\n}" ; "Multi-line")]
+    #[test_case("{\"a\": 1}", "{\"a\": 1, \"b\": true}" ; "Single line")]
+    #[test_case("{\n  \"a\": 1, // one\n}", "{\n  \"a\": 1, // one\n  \"b\": true,\n}" ; "Trailing comma")]
+    #[test_case("{\n  \"a\": 1 // one\n}", "{\n  \"a\": 1, // one\n  \"b\": true\n}" ; "Comment after last value")]
+    #[test_case("{ }", "{\"b\": true }" ; "Empty object")]
+    fn insert(input: &str, expected: &str) {
+        let mut document = jsonc(input);
+        document.insert("", "b", &Value::Bool(true)).unwrap();
+        assert_eq!(document.to_string(), expected);
+    }
+
+    #[test_case("[1, 2, 3]", "/1", "[1, 3]" ; "Middle element")]
+    #[test_case("[1, 2, 3]", "/0", "[2, 3]" ; "First element")]
+    #[test_case("[1, 2, 3]", "/2", "[1, 2]" ; "Last element")]
+    #[test_case("[1, 2,]", "/1", "[1,]" ; "Last element before trailing comma")]
+    #[test_case("[\n  1\n]", "/0", "[\n]" ; "Only element")]
+    #[test_case(
+        "[\n  1, // one\n  2, // two\n  3\n]", "/1", "[\n  1, // one\n  3\n]" ;
+        "Comments on the removed line"
+    )]
+    #[test_case(
+        "{\n  \"a\": 1, // one\n  // about b\n  \"b\": 2 // two\n}", "/b", "{\n  \"a\": 1 // one\n}" ;
+        "Last member with comments"
+    )]
+    fn remove(input: &str, pointer: &str, expected: &str) {
+        let mut document = jsonc(input);
+        document.remove(pointer).unwrap();
+        assert_eq!(document.to_string(), expected);
+    }
+
+    #[test]
+    fn edit_errors() {
+        let mut document = jsonc(r#"{"a": [1]}"#);
+        assert_eq!(
+            document.set("a", &Value::Null),
+            Err(EditError::InvalidPointer("a".to_owned()))
+        );
+        assert_eq!(
+            document.set("/b", &Value::Null),
+            Err(EditError::PathNotFound("/b".to_owned()))
+        );
+        assert_eq!(
+            document.insert("/a", "b", &Value::Null),
+            Err(EditError::NotAnObject("/a".to_owned()))
+        );
+        assert_eq!(
+            document.insert("", "a", &Value::Null),
+            Err(EditError::KeyExists("a".to_owned()))
+        );
+        assert_eq!(
+            document.remove(""),
+            Err(EditError::PathNotFound("".to_owned()))
+        );
+        assert_eq!(document.to_string(), r#"{"a": [1]}"#);
+    }
+}
@@ -0,0 +1,261 @@
+use super::{
+    parse_error::{ParseError, TokenKind},
+    tokenizer::Location,
+};
+use std::fmt::Display;
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const CYAN: &str = "\x1b[1;36m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// Renders a [`ParseError`] for humans: the message, the offending line of
+/// the source with the erroneous token underlined and, where the mistake is
+/// a common one, a hint how to fix it.
+///
+/// ```text
+/// error: Expected `]` or `,`. Received token `2`
+///  --> line 1, column 4
+///   |
+/// 1 | [1 2]
+///   |    ^ did you forget a comma?
+/// ```
+pub struct Diagnostic<'a> {
+    error: &'a ParseError,
+    source: &'a str,
+    color: bool,
+}
+
+impl<'a> Diagnostic<'a> {
+    /// `source` is the input the error was produced for.
+    pub fn new(error: &'a ParseError, source: &'a str) -> Self {
+        Self {
+            error,
+            source,
+            color: false,
+        }
+    }
+
+    /// Highlight the output with ANSI escape codes.
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    fn paint(&self, style: &'static str) -> &'static str {
+        if self.color {
+            style
+        } else {
+            ""
+        }
+    }
+}
+
+impl ParseError {
+    /// Shorthand for [`Diagnostic::new`].
+    pub fn diagnostic<'a>(&'a self, source: &'a str) -> Diagnostic<'a> {
+        Diagnostic::new(self, source)
+    }
+}
+
+impl Display for Diagnostic<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (red, blue, cyan, bold, reset) = (
+            self.paint(RED),
+            self.paint(BLUE),
+            self.paint(CYAN),
+            self.paint(BOLD),
+            self.paint(RESET),
+        );
+        write!(
+            f,
+            "{red}error{reset}{bold}: {}{reset}",
+            self.error.message()
+        )?;
+        let Some(location) = self.error.location() else {
+            return Ok(());
+        };
+        let line_number = location.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+        let (line, indent, underline) = snippet(self.source, &location);
+        write!(
+            f,
+            "\n{gutter}{blue}-->{reset} line {}, column {}\n\
+             {gutter} {blue}|{reset}\n\
+             {blue}{line_number} |{reset} {line}\n\
+             {gutter} {blue}|{reset} {indent}{red}{}{reset}",
+            location.line,
+            location.column,
+            "^".repeat(underline),
+        )?;
+        if let Some(hint) = hint(self.error, self.source) {
+            write!(f, " {cyan}{hint}{reset}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The line containing `location`, the whitespace leading up to the
+/// location and the number of characters to underline, at least one and at
+/// most up to the end of the line.
+fn snippet<'a>(source: &'a str, location: &Location) -> (&'a str, String, usize) {
+    let offset = location.offset.min(source.len());
+    // Like the tokenizer, count a lone `\r` as a line break.
+    let start = source[..offset]
+        .rfind(['\n', '\r'])
+        .map_or(0, |index| index + 1);
+    let end = source[offset..]
+        .find(['\n', '\r'])
+        .map_or(source.len(), |index| offset + index);
+    let line = &source[start..end];
+    // The location may lie within the line break, e.g. at its end.
+    let before = &line[..(offset - start).min(line.len())];
+    // Keep tabs so the underline lines up however wide they are rendered.
+    let indent = before
+        .chars()
+        .map(|char| if char == '\t' { '\t' } else { ' ' })
+        .collect();
+    let remaining = line[before.len()..].chars().count();
+    let underline = location.length.min(remaining).max(1);
+    (line, indent, underline)
+}
+
+/// Advice for mistakes commonly made when writing JSON by hand.
+fn hint(error: &ParseError, source: &str) -> Option<&'static str> {
+    let args = match error {
+        ParseError::UnexpectedToken(args) | ParseError::InvalidToken(args) => args,
+        ParseError::TrailingContent { .. } => {
+            return Some("a document holds a single value, wrap several values in an array")
+        }
+        ParseError::UnexpectedEndOfFile(_) => {
+            return Some("is an object, array or string missing its end?")
+        }
+        ParseError::ControlCharacterInString { .. } => {
+            return Some("escape control characters, e.g. a tab as `\\t`")
+        }
+        ParseError::LoneSurrogate { .. } => {
+            return Some("`ParseOptions::replace_lone_surrogates` replaces it with U+FFFD")
+        }
+        _ => return None,
+    };
+    let value = args.found();
+    let expects = |kind: TokenKind| args.expected().contains(&kind);
+    let previous = source[..args.location().offset.min(source.len())]
+        .trim_end()
+        .chars()
+        .next_back();
+    if value.starts_with('\'') {
+        Some("single quotes are not valid JSON, use double quotes")
+    } else if value.starts_with('/') {
+        Some("comments are not valid JSON, parse as `Dialect::Jsonc` to allow them")
+    } else if matches!(value, "]" | "}") && previous == Some(',') {
+        Some("trailing commas are not valid JSON")
+    } else if expects(TokenKind::ValueSeparator) && value.starts_with(starts_value) {
+        Some("did you forget a comma?")
+    } else if expects(TokenKind::NameSeparator) {
+        Some("did you forget a colon?")
+    } else if expects(TokenKind::String) && value.starts_with(|char: char| char.is_alphabetic()) {
+        Some("object keys must be double-quoted strings")
+    } else {
+        None
+    }
+}
+
+fn starts_value(char: char) -> bool {
+    matches!(char, '"' | '-' | '{' | '[' | 't' | 'f' | 'n' | '0'..='9')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{parse, parse_with, Dialect};
+    use test_case::test_case;
+
+    #[test]
+    fn render() {
+        let json = "{\n  \"a\": [1 2]\n}";
+        let error = parse(json).unwrap_err();
+        assert_eq!(
+            error.diagnostic(json).to_string(),
+            "error: Expected `]` or `,`. Received token `2`\n \
+             --> line 2, column 11\n  \
+             |\n\
+             2 |   \"a\": [1 2]\n  \
+             |           ^ did you forget a comma?"
+        );
+    }
+
+    #[test]
+    fn color() {
+        let json = "[1,]";
+        let error = parse(json).unwrap_err();
+        let rendered = error.diagnostic(json).color(true).to_string();
+        assert!(rendered.starts_with("\x1b[1;31merror\x1b[0m"));
+        assert!(rendered.contains("\x1b[1;31m^\x1b[0m"));
+    }
+
+    #[test_case("['abc']", "^", Some("single quotes are not valid JSON, use double quotes") ; "Single quotes")]
+    #[test_case("[1 // note\n]", "^", Some("comments are not valid JSON, parse as `Dialect::Jsonc` to allow them") ; "Comment")]
+    #[test_case("[1, 2,\n]", "^", Some("trailing commas are not valid JSON") ; "Trailing comma")]
+    #[test_case(r#"{"a": 1 "b": 2}"#, "^^^", Some("did you forget a comma?") ; "Missing comma")]
+    #[test_case(r#"{"a" 1}"#, "^", Some("did you forget a colon?") ; "Missing colon")]
+    #[test_case("{key: 1}", "^^^", Some("object keys must be double-quoted strings") ; "Unquoted key")]
+    #[test_case("[\"a\tb\"]", "^", Some("escape control characters, e.g. a tab as `\\t`") ; "Raw tab")]
+    #[test_case(r#"["\uDC00"]"#, "^^^^^^", Some("`ParseOptions::replace_lone_surrogates` replaces it with U+FFFD") ; "Lone surrogate")]
+    #[test_case("[1, 01]", "^", None ; "No hint")]
+    fn hints(json: &str, underline: &str, hint: Option<&str>) {
+        let error = parse(json).unwrap_err();
+        let rendered = error.diagnostic(json).to_string();
+        let last_line = rendered.lines().last().unwrap();
+        let (_, marker) = last_line.split_once("| ").unwrap();
+        let expected = match hint {
+            Some(hint) => format!("{} {}", underline, hint),
+            None => underline.to_string(),
+        };
+        assert_eq!(marker.trim_start(), expected);
+    }
+
+    #[test]
+    fn tabs_and_line_ends() {
+        let json = "[\r\n\t\ttrue false\r\n]";
+        let error = parse(json).unwrap_err();
+        let rendered = error.diagnostic(json).to_string();
+        assert!(
+            rendered.ends_with("2 | \t\ttrue false\n  | \t\t     ^^^^^ did you forget a comma?")
+        );
+    }
+
+    #[test]
+    fn end_of_file() {
+        let json = "[1, 2";
+        let error = parse(json).unwrap_err();
+        assert!(error
+            .diagnostic(json)
+            .to_string()
+            .ends_with("1 | [1, 2\n  |      ^ is an object, array or string missing its end?"));
+    }
+
+    #[test_case("[\r" ; "Unterminated array")]
+    #[test_case("\r" ; "Only a line break")]
+    #[test_case(" {\r" ; "Unterminated object")]
+    #[test_case("{true\r" ; "Unquoted key")]
+    #[test_case("[1,\r\n" ; "Line feed after carriage return")]
+    fn carriage_return_at_end(json: &str) {
+        for dialect in [Dialect::Json, Dialect::Jsonc, Dialect::Json5] {
+            let error = parse_with(json, dialect).unwrap_err();
+            let line = error.location().unwrap().line;
+            let rendered = error.diagnostic(json).to_string();
+            assert!(rendered.contains(&format!("\n{} | ", line)), "{}", rendered);
+        }
+    }
+
+    #[test]
+    fn lone_carriage_return() {
+        let json = "[1,\r2";
+        let error = parse(json).unwrap_err();
+        assert!(error
+            .diagnostic(json)
+            .to_string()
+            .ends_with("2 | 2\n  |  ^ is an object, array or string missing its end?"));
+    }
+}
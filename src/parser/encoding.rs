@@ -0,0 +1,144 @@
+use std::{borrow::Cow, fmt::Display, str};
+
+/// Encodings JSON text may arrive in. RFC 8259 requires UTF-8 for exchange,
+/// but §8.1 of its predecessor RFC 7159 still allows UTF-16 and UTF-32.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Utf32Le,
+    Utf32Be,
+}
+
+impl Encoding {
+    /// Detects the encoding from a byte order mark or, lacking one, from the
+    /// zero bytes among the first four: the first two characters of a JSON
+    /// text are ASCII, so their zero bytes reveal width and byte order.
+    pub fn detect(bytes: &[u8]) -> Self {
+        match bytes {
+            [0xEF, 0xBB, 0xBF, ..] => Self::Utf8,
+            [0x00, 0x00, 0xFE, 0xFF, ..] | [0x00, 0x00, 0x00, _, ..] => Self::Utf32Be,
+            [0xFF, 0xFE, 0x00, 0x00, ..] | [_, 0x00, 0x00, 0x00, ..] => Self::Utf32Le,
+            [0xFE, 0xFF, ..] | [0x00, _, ..] => Self::Utf16Be,
+            [0xFF, 0xFE, ..] | [_, 0x00, ..] => Self::Utf16Le,
+            _ => Self::Utf8,
+        }
+    }
+}
+
+impl Display for Encoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Utf8 => "UTF-8",
+            Self::Utf16Le => "UTF-16LE",
+            Self::Utf16Be => "UTF-16BE",
+            Self::Utf32Le => "UTF-32LE",
+            Self::Utf32Be => "UTF-32BE",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Decodes `bytes` as far as they are valid in their detected encoding.
+/// Returns the text and, if decoding stopped early, the encoding together
+/// with the offset in the text at which it did. A byte order mark is kept
+/// as U+FEFF for the tokenizer to skip.
+pub fn decode(bytes: &[u8]) -> (Cow<'_, str>, Option<(Encoding, usize)>) {
+    let encoding = Encoding::detect(bytes);
+    let (text, is_complete) = match encoding {
+        Encoding::Utf8 => {
+            let (text, is_complete) = decode_utf8(bytes);
+            (Cow::Borrowed(text), is_complete)
+        }
+        Encoding::Utf16Le | Encoding::Utf16Be => {
+            let units = bytes.chunks_exact(2);
+            let mut is_complete = units.remainder().is_empty();
+            let units = units.map(|unit| {
+                let unit = [unit[0], unit[1]];
+                if encoding == Encoding::Utf16Le {
+                    u16::from_le_bytes(unit)
+                } else {
+                    u16::from_be_bytes(unit)
+                }
+            });
+            let mut text = String::with_capacity(bytes.len() / 2);
+            for character in char::decode_utf16(units) {
+                match character {
+                    Ok(character) => text.push(character),
+                    Err(_) => {
+                        is_complete = false;
+                        break;
+                    }
+                }
+            }
+            (Cow::Owned(text), is_complete)
+        }
+        Encoding::Utf32Le | Encoding::Utf32Be => {
+            let units = bytes.chunks_exact(4);
+            let mut text = String::with_capacity(bytes.len() / 4);
+            let mut is_complete = units.remainder().is_empty();
+            for unit in units {
+                let unit = [unit[0], unit[1], unit[2], unit[3]];
+                let code_point = if encoding == Encoding::Utf32Le {
+                    u32::from_le_bytes(unit)
+                } else {
+                    u32::from_be_bytes(unit)
+                };
+                match char::from_u32(code_point) {
+                    Some(character) => text.push(character),
+                    None => {
+                        is_complete = false;
+                        break;
+                    }
+                }
+            }
+            (Cow::Owned(text), is_complete)
+        }
+    };
+    let error = (!is_complete).then_some((encoding, text.len()));
+    (text, error)
+}
+
+/// The valid UTF-8 prefix of `bytes` and whether it spans all of them.
+pub(crate) fn decode_utf8(bytes: &[u8]) -> (&str, bool) {
+    match str::from_utf8(bytes) {
+        Ok(text) => (text, true),
+        Err(error) => {
+            let valid = &bytes[..error.valid_up_to()];
+            (str::from_utf8(valid).expect("the prefix is valid"), false)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(b"{}", Encoding::Utf8 ; "ASCII")]
+    #[test_case(b"\xEF\xBB\xBF[]", Encoding::Utf8 ; "UTF-8 with byte order mark")]
+    #[test_case(b"[\x00]\x00", Encoding::Utf16Le ; "UTF-16LE")]
+    #[test_case(b"\x00[\x00]", Encoding::Utf16Be ; "UTF-16BE")]
+    #[test_case(b"\xFF\xFE[\x00", Encoding::Utf16Le ; "UTF-16LE with byte order mark")]
+    #[test_case(b"\xFE\xFF\x00[", Encoding::Utf16Be ; "UTF-16BE with byte order mark")]
+    #[test_case(b"1\x00\x00\x00", Encoding::Utf32Le ; "UTF-32LE")]
+    #[test_case(b"\x00\x00\x001", Encoding::Utf32Be ; "UTF-32BE")]
+    #[test_case(b"\xFF\xFE\x00\x00", Encoding::Utf32Le ; "UTF-32LE with byte order mark")]
+    #[test_case(b"\x00\x00\xFE\xFF", Encoding::Utf32Be ; "UTF-32BE with byte order mark")]
+    #[test_case(b"1", Encoding::Utf8 ; "Single byte")]
+    #[test_case(b"", Encoding::Utf8 ; "Empty input")]
+    fn detect(bytes: &[u8], expected: Encoding) {
+        assert_eq!(Encoding::detect(bytes), expected);
+    }
+
+    #[test_case(b"[\"\xC3\xBC\"]", "[\"ü\"]", None ; "UTF-8")]
+    #[test_case(b"[1, \xC3(]", "[1, ", Some((Encoding::Utf8, 4)) ; "Invalid UTF-8")]
+    #[test_case(b"\xFF\xFE[\x00\x3D\xD8\x1E\xDD]\x00", "\u{FEFF}[\u{1F51E}]", None ; "UTF-16LE surrogate pair")]
+    #[test_case(b"\x00[\xD8\x3D\x00]", "[", Some((Encoding::Utf16Be, 1)) ; "Unpaired UTF-16 surrogate")]
+    #[test_case(b"[\x00]", "[", Some((Encoding::Utf16Le, 1)) ; "Odd UTF-16 length")]
+    #[test_case(b"\x00\x00\x00[\x00\x11\x00\x00", "[", Some((Encoding::Utf32Be, 1)) ; "UTF-32 beyond Unicode")]
+    fn decode_bytes(bytes: &[u8], text: &str, error: Option<(Encoding, usize)>) {
+        assert_eq!(decode(bytes), (Cow::Borrowed(text), error));
+    }
+}
@@ -0,0 +1,452 @@
+use super::{
+    number::Number,
+    options::ParseOptions,
+    parse_error::{ParseError, ParseErrorArgs, TokenKind},
+    parser::{
+        check_duplicate, check_members, next_token, property_key, seen_keys, unexpected_value,
+        SeenKeys,
+    },
+    source::{ReadSource, Source, StrSource},
+    tokenizer::{Location, Token, TokenType, Tokenizer},
+};
+use std::{borrow::Cow, io::Read};
+
+#[derive(Debug, PartialEq)]
+pub enum Scalar<'a> {
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(Cow<'a, str>),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Event<'a> {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+    Key(Cow<'a, str>),
+    Scalar(Scalar<'a>),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Container {
+    Object,
+    Array,
+}
+
+/// An open container.
+struct Frame {
+    container: Container,
+    members: usize,
+    keys: SeenKeys,
+}
+
+/// Position within the grammar, i.e. what the next token may be.
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    /// The top-level value.
+    Document,
+    /// First element or `]` right after `[`.
+    ArrayStart,
+    /// Element after `,`.
+    ArrayElement,
+    /// First key or `}` right after `{`.
+    ObjectStart,
+    /// Key after `,`.
+    ObjectKey,
+    /// `:` following a key.
+    AfterKey,
+    /// Value after `:`.
+    ObjectValue,
+    /// `,` or the end of the enclosing container.
+    AfterValue,
+    /// End of the input after the top-level value.
+    EndOfDocument,
+    Finished,
+}
+
+/// Pull parser yielding the [`Event`]s of a JSON document one by one while
+/// validating them against the JSON grammar. Memory usage only grows with
+/// the nesting depth, not with the size of the document.
+///
+/// After the first error the iterator is exhausted.
+pub struct JsonEvents<'a, S: Source<'a> = StrSource<'a>> {
+    tokenizer: Tokenizer<'a, S>,
+    stack: Vec<Frame>,
+    state: State,
+    values: usize,
+    /// Whether further documents may follow the first one.
+    is_sequence: bool,
+}
+
+impl<'a> JsonEvents<'a> {
+    pub fn new(json: &'a str) -> Self {
+        Self::with_options(json, ParseOptions::default())
+    }
+
+    pub fn with_options(json: &'a str, options: ParseOptions) -> Self {
+        Self::from_tokenizer(Tokenizer::with_options(json, options))
+    }
+}
+
+impl<R: Read> JsonEvents<'static, ReadSource<R>> {
+    pub fn from_reader(reader: R) -> Self {
+        Self::from_reader_with(reader, ParseOptions::default())
+    }
+
+    pub fn from_reader_with(reader: R, options: ParseOptions) -> Self {
+        Self::from_tokenizer(Tokenizer::from_reader(reader, options))
+    }
+}
+
+impl<'a, S: Source<'a>> JsonEvents<'a, S> {
+    pub(crate) fn from_tokenizer(tokenizer: Tokenizer<'a, S>) -> Self {
+        Self {
+            tokenizer,
+            stack: Vec::new(),
+            state: State::Document,
+            values: 0,
+            is_sequence: false,
+        }
+    }
+
+    /// Accept any number of documents one after another instead of exactly
+    /// one, like [`ValueStream`](super::ValueStream).
+    pub(crate) fn sequence(mut self) -> Self {
+        self.is_sequence = true;
+        self
+    }
+
+    pub(crate) fn tokenizer_mut(&mut self) -> &mut Tokenizer<'a, S> {
+        &mut self.tokenizer
+    }
+
+    pub(crate) fn is_finished(&self) -> bool {
+        self.state == State::Finished
+    }
+
+    /// Whether no document is partially read.
+    pub(crate) fn is_between_documents(&self) -> bool {
+        matches!(self.state, State::Document | State::EndOfDocument)
+    }
+
+    /// Counts the member starting with `token` towards the innermost container.
+    fn member(&mut self, token: &Token) -> Result<(), ParseError> {
+        if let Some(frame) = self.stack.last_mut() {
+            frame.members += 1;
+            check_members(&self.tokenizer, frame.members, token)?;
+        }
+        Ok(())
+    }
+
+    fn value(&mut self, token: Token<'a>) -> Result<Event<'a>, ParseError> {
+        self.values += 1;
+        if self.values > self.tokenizer.options().max_values {
+            return Err(ParseError::ValueLimitExceeded {
+                location: token.location,
+                limit: self.tokenizer.options().max_values,
+            });
+        }
+        let event = match token.token_type {
+            TokenType::Null => Event::Scalar(Scalar::Null),
+            TokenType::True => Event::Scalar(Scalar::Bool(true)),
+            TokenType::False => Event::Scalar(Scalar::Bool(false)),
+            TokenType::Number(number) => Event::Scalar(Scalar::Number(number)),
+            TokenType::String(string) => Event::Scalar(Scalar::String(string)),
+            TokenType::BeginObject | TokenType::BeginArray
+                if self.stack.len() >= self.tokenizer.options().max_depth =>
+            {
+                return Err(ParseError::DepthLimitExceeded {
+                    location: token.location,
+                    limit: self.tokenizer.options().max_depth,
+                })
+            }
+            TokenType::BeginObject => {
+                self.stack.push(Frame {
+                    container: Container::Object,
+                    members: 0,
+                    keys: seen_keys(&self.tokenizer),
+                });
+                self.state = State::ObjectStart;
+                return Ok(Event::StartObject);
+            }
+            TokenType::BeginArray => {
+                self.stack.push(Frame {
+                    container: Container::Array,
+                    members: 0,
+                    keys: None,
+                });
+                self.state = State::ArrayStart;
+                return Ok(Event::StartArray);
+            }
+            _ => return Err(unexpected_value(token)),
+        };
+        self.state = State::AfterValue;
+        Ok(event)
+    }
+
+    fn key(&mut self, token: Token<'a>) -> Result<Event<'a>, ParseError> {
+        let location = token.location;
+        let key = property_key(&self.tokenizer, token)?;
+        if let Some(frame) = self.stack.last_mut() {
+            check_duplicate(&mut frame.keys, &key, location)?;
+        }
+        self.state = State::AfterKey;
+        Ok(Event::Key(key))
+    }
+
+    fn end_container(&mut self) -> Event<'a> {
+        self.state = State::AfterValue;
+        match self.stack.pop() {
+            Some(Frame {
+                container: Container::Object,
+                ..
+            }) => Event::EndObject,
+            _ => Event::EndArray,
+        }
+    }
+
+    /// Advances the grammar by the token read next, `None` standing for the
+    /// end of input, and returns the event it completes. Separators complete
+    /// none. After an error, no further events follow.
+    pub(crate) fn step(
+        &mut self,
+        token: Result<Option<Token<'a>>, ParseError>,
+    ) -> Result<Option<(Event<'a>, Location)>, ParseError> {
+        let result = token.and_then(|token| self.transition(token));
+        if self.state == State::AfterValue && self.stack.is_empty() {
+            if self.is_sequence {
+                self.state = State::Document;
+                self.values = 0;
+            } else {
+                self.state = State::EndOfDocument;
+            }
+        }
+        if result.is_err() {
+            self.state = State::Finished;
+        }
+        result
+    }
+
+    fn transition(
+        &mut self,
+        token: Option<Token<'a>>,
+    ) -> Result<Option<(Event<'a>, Location)>, ParseError> {
+        let token = match (self.state, token) {
+            (State::Finished, _) => return Ok(None),
+            (State::EndOfDocument, Some(token)) => return Err(ParseError::trailing_content(token)),
+            (State::EndOfDocument, None) => {
+                self.state = State::Finished;
+                return Ok(None);
+            }
+            (State::Document, None) if self.is_sequence => {
+                self.state = State::Finished;
+                return Ok(None);
+            }
+            (_, None) => {
+                return Err(ParseError::UnexpectedEndOfFile(
+                    self.tokenizer.end_location(),
+                ))
+            }
+            (_, Some(token)) => token,
+        };
+        let location = token.location;
+        let event = match (self.state, &token.token_type) {
+            (State::ArrayStart, TokenType::EndArray)
+            | (State::ObjectStart, TokenType::EndObject) => self.end_container(),
+            (State::ArrayElement, TokenType::EndArray)
+            | (State::ObjectKey, TokenType::EndObject)
+                if self.tokenizer.options().dialect.allows_trailing_commas() =>
+            {
+                self.end_container()
+            }
+            (State::Document | State::ObjectValue, _) => self.value(token)?,
+            (State::ArrayStart | State::ArrayElement, _) => {
+                self.member(&token)?;
+                self.value(token)?
+            }
+            (State::ObjectStart | State::ObjectKey, _) => {
+                self.member(&token)?;
+                self.key(token)?
+            }
+            (State::AfterKey, TokenType::NameSeparator) => {
+                self.state = State::ObjectValue;
+                return Ok(None);
+            }
+            (State::AfterKey, _) => {
+                return Err(ParseError::UnexpectedToken(ParseErrorArgs::new(
+                    token,
+                    vec![TokenKind::NameSeparator],
+                )))
+            }
+            (State::AfterValue, _) => {
+                let container = self
+                    .stack
+                    .last()
+                    .expect("containers are open after values")
+                    .container;
+                match (container, &token.token_type) {
+                    (Container::Object, TokenType::EndObject)
+                    | (Container::Array, TokenType::EndArray) => self.end_container(),
+                    (Container::Object, TokenType::ValueSeparator) => {
+                        self.state = State::ObjectKey;
+                        return Ok(None);
+                    }
+                    (Container::Array, TokenType::ValueSeparator) => {
+                        self.state = State::ArrayElement;
+                        return Ok(None);
+                    }
+                    (Container::Object, _) => {
+                        return Err(ParseError::UnexpectedToken(ParseErrorArgs::new(
+                            token,
+                            vec![TokenKind::EndObject, TokenKind::ValueSeparator],
+                        )))
+                    }
+                    (Container::Array, _) => {
+                        return Err(ParseError::UnexpectedToken(ParseErrorArgs::new(
+                            token,
+                            vec![TokenKind::EndArray, TokenKind::ValueSeparator],
+                        )))
+                    }
+                }
+            }
+            (State::EndOfDocument | State::Finished, _) => unreachable!(),
+        };
+        Ok(Some((event, location)))
+    }
+}
+
+impl<'a, S: Source<'a>> Iterator for JsonEvents<'a, S> {
+    type Item = Result<(Event<'a>, Location), ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.is_finished() {
+            let token = next_token(&mut self.tokenizer);
+            if let Some(result) = self.step(token).transpose() {
+                return Some(result);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{Dialect, DuplicateKeyPolicy};
+    use test_case::test_case;
+
+    fn collect_events(json: &str) -> Result<Vec<Event<'_>>, ParseError> {
+        JsonEvents::new(json)
+            .map(|result| result.map(|(event, _)| event))
+            .collect()
+    }
+
+    #[test]
+    fn document_events() {
+        let events = collect_events(r#"{"a": [1, {}], "b": null, "c": []}"#).unwrap();
+        assert_eq!(
+            events,
+            vec![
+                Event::StartObject,
+                Event::Key(Cow::from("a")),
+                Event::StartArray,
+                Event::Scalar(Scalar::Number(Number::UnsingedInteger(1))),
+                Event::StartObject,
+                Event::EndObject,
+                Event::EndArray,
+                Event::Key(Cow::from("b")),
+                Event::Scalar(Scalar::Null),
+                Event::Key(Cow::from("c")),
+                Event::StartArray,
+                Event::EndArray,
+                Event::EndObject,
+            ]
+        );
+    }
+
+    #[test]
+    fn scalar_document() {
+        let events = collect_events(r#""text""#).unwrap();
+        assert_eq!(
+            events,
+            vec![Event::Scalar(Scalar::String(Cow::from("text")))]
+        );
+    }
+
+    #[test]
+    fn event_locations() {
+        let locations: Vec<(usize, usize)> = JsonEvents::new("[\n  true,\n  \"x\"\n]")
+            .map(|result| result.unwrap().1)
+            .map(|location| (location.line, location.column))
+            .collect();
+        assert_eq!(locations, vec![(1, 1), (2, 3), (3, 3), (4, 1)]);
+    }
+
+    #[test_case("[1 2]" ; "Missing comma")]
+    #[test_case(r#"{"a" 1}"# ; "Missing colon")]
+    #[test_case(r#"{1: 2}"# ; "Non-string key")]
+    #[test_case("[1,]" ; "Trailing comma")]
+    #[test_case("[1}" ; "Mismatched bracket")]
+    #[test_case("[1] 2" ; "Trailing content")]
+    #[test_case("[[" ; "Unexpected end of file")]
+    #[test_case("" ; "Empty input")]
+    fn invalid_documents(json: &str) {
+        let mut events = JsonEvents::new(json);
+        assert!(events.by_ref().any(|result| result.is_err()));
+        assert!(events.next().is_none());
+    }
+
+    #[test]
+    fn deep_nesting() {
+        let json = "[".repeat(100_000);
+        let error = JsonEvents::new(&json).find_map(Result::err);
+        assert!(matches!(
+            error,
+            Some(ParseError::DepthLimitExceeded { limit: 128, .. })
+        ));
+    }
+
+    #[test_case(ParseOptions::new().max_members(2), "[1, 2, 3]" ; "Members")]
+    #[test_case(ParseOptions::new().max_values(3), r#"{"a": [1, 2]}"# ; "Values")]
+    #[test_case(ParseOptions::new().max_string_length(2), r#"["abc"]"# ; "String length")]
+    #[test_case(ParseOptions::new().max_input_size(2), "[1]" ; "Input size")]
+    fn limits(options: ParseOptions, json: &str) {
+        let error = JsonEvents::with_options(json, options.clone()).find_map(Result::err);
+        let expected = crate::parser::parse_with(json, options).unwrap_err();
+        assert_eq!(error.unwrap().to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let json = r#"{"a": {"a": 1}, "b": 2, "a": 3}"#;
+        let options = ParseOptions::new().duplicate_keys(DuplicateKeyPolicy::Error);
+        let error = JsonEvents::with_options(json, options).find_map(Result::err);
+        assert!(matches!(
+            error,
+            Some(ParseError::DuplicateKey { key, original, duplicate })
+                if key == "a" && original.column == 2 && duplicate.column == 25
+        ));
+        assert!(JsonEvents::new(json).all(|result| result.is_ok()));
+    }
+
+    #[test]
+    fn trailing_commas() {
+        let json = "[1, {\"a\": 2,},] // done";
+        let options = ParseOptions::new().dialect(Dialect::Jsonc);
+        let events: Vec<Event> = JsonEvents::with_options(json, options)
+            .map(|result| result.unwrap().0)
+            .collect();
+        assert_eq!(events, collect_events(r#"[1, {"a": 2}]"#).unwrap());
+    }
+
+    #[test]
+    fn reader_events() {
+        let json = r#"{"a": ["b", false]}"#;
+        let from_reader: Vec<Event> = JsonEvents::from_reader(json.as_bytes())
+            .map(|result| result.unwrap().0)
+            .collect();
+        assert_eq!(from_reader, collect_events(json).unwrap());
+    }
+}
@@ -0,0 +1,444 @@
+use super::{
+    events::{Event, JsonEvents, Scalar},
+    options::{Dialect, ParseOptions},
+    parse_error::ParseError,
+    parser::next_token,
+    source::{ChunkSource, Source},
+    tokenizer::{is_letter, is_number_character, Location, Tokenizer},
+    value::Value,
+    visitor::{TreeBuilder, Visitor},
+};
+use std::mem;
+
+/// Push parser for input arriving in chunks, e.g. from a socket. Chunks
+/// are handed over with [`feed`](IncrementalParser::feed) as they come in
+/// and may split the input anywhere, even within a string, an escape
+/// sequence, a number or a UTF-8 character. Events and values are available
+/// as soon as the input fed so far completes them.
+///
+/// The input may hold any number of values one after another, like
+/// `{"a":1}{"b":2}`. [`finish`](IncrementalParser::finish) marks its end,
+/// whereupon a value cut short is reported as an error.
+///
+/// Read the input either as events with
+/// [`next_event`](IncrementalParser::next_event) or as values with
+/// [`next_value`](IncrementalParser::next_value), not both. A token split
+/// across chunks is read again from its start once the input fed so far
+/// may complete it, so a long string arriving in many chunks is still read
+/// in linear time.
+pub struct IncrementalParser {
+    events: JsonEvents<'static, ChunkSource>,
+    builder: TreeBuilder<'static, Value>,
+    is_byte_order_mark_checked: bool,
+    /// Follows the token that last ran out of input.
+    lookahead: Option<Lookahead>,
+}
+
+impl Default for IncrementalParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IncrementalParser {
+    pub fn new() -> Self {
+        Self::with_options(ParseOptions::default())
+    }
+
+    pub fn with_options(options: ParseOptions) -> Self {
+        let mut parser = Self::document(options);
+        parser.events = parser.events.sequence();
+        parser
+    }
+
+    /// Parser for input holding exactly one value, like [`parse`](super::parse).
+    pub(crate) fn document(options: ParseOptions) -> Self {
+        let builder = TreeBuilder::with_duplicate_keys(options.duplicate_keys);
+        let tokenizer = Tokenizer::without_byte_order_mark(ChunkSource::new(), options);
+        Self {
+            events: JsonEvents::from_tokenizer(tokenizer),
+            builder,
+            is_byte_order_mark_checked: false,
+            lookahead: None,
+        }
+    }
+
+    /// Appends the next chunk of input.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.source().feed(bytes);
+    }
+
+    /// Marks the end of input. Nothing fed afterwards is read.
+    pub fn finish(&mut self) {
+        self.source().finish();
+    }
+
+    fn source(&mut self) -> &mut ChunkSource {
+        self.events.tokenizer_mut().source_mut()
+    }
+
+    /// The next event completed by the input fed so far, `None` if it takes
+    /// more input or the input is finished. After an error, no further
+    /// events follow.
+    pub fn next_event(&mut self) -> Option<Result<(Event<'static>, Location), ParseError>> {
+        while !self.events.is_finished() {
+            let tokenizer = self.events.tokenizer_mut();
+            if !self.is_byte_order_mark_checked {
+                // Only a complete first character tells whether it is one.
+                let source = tokenizer.source_mut();
+                if source.peek(0).is_none() && source.take_starved() {
+                    return None;
+                }
+                tokenizer.skip_byte_order_mark();
+                tokenizer.source_mut().take_starved();
+                self.is_byte_order_mark_checked = true;
+            }
+            if let Some(lookahead) = &mut self.lookahead {
+                if !lookahead.scan(tokenizer.source(), tokenizer.options()) {
+                    return None;
+                }
+            }
+            let (line, column, offset) = (tokenizer.line, tokenizer.column, tokenizer.offset());
+            let token = next_token(tokenizer);
+            if tokenizer.source_mut().take_starved() {
+                // The token, or the whitespace before it, may continue in
+                // the next chunk.
+                tokenizer.source_mut().rewind(offset);
+                tokenizer.line = line;
+                tokenizer.column = column;
+                self.lookahead.get_or_insert(Lookahead {
+                    position: offset,
+                    state: Scan::Whitespace,
+                });
+                return None;
+            }
+            self.lookahead = None;
+            tokenizer.source_mut().commit();
+            if let Some(result) = self.events.step(token).transpose() {
+                return Some(result);
+            }
+        }
+        None
+    }
+
+    /// The next value completed by the input fed so far, `None` if it takes
+    /// more input or the input is finished.
+    pub fn next_value(&mut self) -> Option<Result<Value, ParseError>> {
+        while let Some(result) = self.next_event() {
+            match result {
+                Ok((event, _)) => {
+                    let Ok(()) = visit(&mut self.builder, event);
+                }
+                Err(error) => return Some(Err(error)),
+            }
+            if self.events.is_between_documents() {
+                let duplicate_keys = self.events.tokenizer_mut().options().duplicate_keys;
+                let builder = TreeBuilder::with_duplicate_keys(duplicate_keys);
+                return mem::replace(&mut self.builder, builder).finish().map(Ok);
+            }
+        }
+        None
+    }
+}
+
+/// Scans a token that ran out of input as more input arrives, each byte
+/// once, so that the tokenizer only reads the token again once it may be
+/// complete instead of after every chunk.
+struct Lookahead {
+    /// Absolute offset of the next byte to scan.
+    position: usize,
+    state: Scan,
+}
+
+#[derive(Clone, Copy)]
+enum Scan {
+    /// Whitespace before the token.
+    Whitespace,
+    /// A `/` which may start a comment.
+    Slash,
+    LineComment,
+    BlockComment {
+        is_star: bool,
+    },
+    String {
+        quote: u8,
+        is_escaped: bool,
+        length: usize,
+    },
+    Number,
+    Literal,
+    /// The token may be complete, or the scan cannot tell.
+    Ready,
+}
+
+impl Lookahead {
+    /// Scans the input received since the last call, returning whether it
+    /// may complete the token.
+    fn scan(&mut self, source: &ChunkSource, options: &ParseOptions) -> bool {
+        for &byte in source.received(self.position) {
+            if matches!(self.state, Scan::Ready) {
+                break;
+            }
+            self.state = if self.position < options.max_input_size {
+                self.state.next(byte, options)
+            } else {
+                Scan::Ready
+            };
+            self.position += 1;
+        }
+        matches!(self.state, Scan::Ready) || source.is_complete()
+    }
+}
+
+impl Scan {
+    /// The state after `byte`, which becomes [`Scan::Ready`] wherever the
+    /// tokenizer might stop reading.
+    fn next(self, byte: u8, options: &ParseOptions) -> Self {
+        let json5 = options.dialect == Dialect::Json5;
+        match (self, byte) {
+            (Scan::Whitespace, b' ' | b'\t' | b'\n' | b'\r') => Scan::Whitespace,
+            (Scan::Whitespace, 0x0B | 0x0C) if json5 => Scan::Whitespace,
+            (Scan::Whitespace, b'/') if options.dialect.allows_comments() => Scan::Slash,
+            (Scan::Whitespace, quote @ (b'"' | b'\'')) if quote == b'"' || json5 => Scan::String {
+                quote,
+                is_escaped: false,
+                length: 0,
+            },
+            (Scan::Whitespace, byte)
+                if json5 && (is_letter(byte) || matches!(byte, b'$' | b'_')) =>
+            {
+                Scan::Literal
+            }
+            (Scan::Whitespace, byte) if !json5 && is_letter(byte) => Scan::Literal,
+            (Scan::Whitespace, byte) if is_number_character(byte) => Scan::Number,
+            (Scan::Slash, b'/') => Scan::LineComment,
+            (Scan::Slash, b'*') => Scan::BlockComment { is_star: false },
+            (Scan::LineComment, b'\n' | b'\r') => Scan::Whitespace,
+            (Scan::LineComment, _) => Scan::LineComment,
+            (Scan::BlockComment { is_star: true }, b'/') => Scan::Whitespace,
+            (Scan::BlockComment { .. }, byte) => Scan::BlockComment {
+                is_star: byte == b'*',
+            },
+            (
+                Scan::String {
+                    quote,
+                    is_escaped,
+                    length,
+                },
+                byte,
+            ) => match byte {
+                _ if length >= options.max_string_length => Scan::Ready,
+                0x00..=0x1F if !json5 || matches!(byte, b'\n' | b'\r') => Scan::Ready,
+                _ if is_escaped => Scan::String {
+                    quote,
+                    is_escaped: false,
+                    length: length + 1,
+                },
+                _ if byte == quote => Scan::Ready,
+                _ => Scan::String {
+                    quote,
+                    is_escaped: byte == b'\\',
+                    length: length + 1,
+                },
+            },
+            (Scan::Number, byte) if is_number_character(byte) => Scan::Number,
+            (Scan::Number | Scan::Literal, byte) if json5 && byte.is_ascii_alphanumeric() => self,
+            (Scan::Literal, b'$' | b'_') if json5 => Scan::Literal,
+            (Scan::Literal, byte) if is_letter(byte) => Scan::Literal,
+            _ => Scan::Ready,
+        }
+    }
+}
+
+fn visit<'a, V: Visitor<'a>>(visitor: &mut V, event: Event<'a>) -> Result<(), V::Error> {
+    match event {
+        Event::StartObject => visitor.begin_object(),
+        Event::EndObject => visitor.end_object(),
+        Event::StartArray => visitor.begin_array(),
+        Event::EndArray => visitor.end_array(),
+        Event::Key(key) => visitor.visit_key(key),
+        Event::Scalar(Scalar::Null) => visitor.visit_null(),
+        Event::Scalar(Scalar::Bool(bool)) => visitor.visit_bool(bool),
+        Event::Scalar(Scalar::Number(number)) => visitor.visit_number(number),
+        Event::Scalar(Scalar::String(string)) => visitor.visit_string(string),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse, parse_with, ErrorKind, JsonEvents};
+    use test_case::test_case;
+
+    /// Feeds `input` in chunks of `size` bytes, collecting the values
+    /// completed after every chunk.
+    fn values_in_chunks(input: &[u8], size: usize) -> Vec<Result<Value, ErrorKind>> {
+        let mut parser = IncrementalParser::new();
+        let mut values = Vec::new();
+        for chunk in input.chunks(size) {
+            parser.feed(chunk);
+            values.extend(std::iter::from_fn(|| parser.next_value()));
+        }
+        parser.finish();
+        values.extend(std::iter::from_fn(|| parser.next_value()));
+        values
+            .into_iter()
+            .map(|result| result.map_err(|error| error.kind()))
+            .collect()
+    }
+
+    #[test_case(r#"{"key": "value", "list": [1, -2.5e3, true, null, {}]}"# ; "Object")]
+    #[test_case(r#""esc\"ape\\ ü 𝄞""# ; "Escape sequences")]
+    #[test_case("\"ü 🎉 ∑\"" ; "Multibyte characters")]
+    #[test_case("-12.75E+8" ; "Number")]
+    #[test_case("\r\n[\r\n  1,\r\n  2\r\n]\r\n" ; "Line breaks")]
+    #[test_case("\u{FEFF}[\"bom\"]" ; "Byte order mark")]
+    fn chunk_boundaries(json: &str) {
+        for size in 1..=json.len() {
+            assert_eq!(
+                values_in_chunks(json.as_bytes(), size),
+                vec![Ok(parse(json).unwrap())],
+                "chunks of {} bytes",
+                size
+            );
+        }
+    }
+
+    #[test_case(Dialect::Jsonc, "[1, /* a * / b **/ 2, // c\r\n 3,]" ; "JSONC")]
+    #[test_case(
+        Dialect::Json5,
+        "{key: 'it\\'s', $x_1: 0x1F, y: -Infinity, s: \"a\\\r\nb\", t: +.5, /**/ u: [Infinity,],}" ;
+        "JSON5"
+    )]
+    fn chunk_boundaries_in_dialect(dialect: Dialect, json: &str) {
+        for size in 1..=json.len() {
+            let mut parser = IncrementalParser::with_options(ParseOptions::from(dialect));
+            let mut values = Vec::new();
+            for chunk in json.as_bytes().chunks(size) {
+                parser.feed(chunk);
+                values.extend(std::iter::from_fn(|| parser.next_value()));
+            }
+            parser.finish();
+            values.extend(std::iter::from_fn(|| parser.next_value()));
+            let values: Vec<_> = values.into_iter().collect::<Result<_, _>>().unwrap();
+            assert_eq!(
+                values,
+                vec![parse_with(json, dialect).unwrap()],
+                "chunks of {} bytes",
+                size
+            );
+        }
+    }
+
+    #[test]
+    fn long_string_in_small_chunks() {
+        let text = "abcdefgh\\u00fc".repeat(1 << 19);
+        let json = format!("[\"{text}\"]");
+        let chunks: Vec<_> = json.as_bytes().chunks(4096).collect();
+        let (last, chunks) = chunks.split_last().unwrap();
+        let mut parser = IncrementalParser::new();
+        let mut received = 0;
+        for (index, chunk) in chunks.iter().enumerate() {
+            parser.feed(chunk);
+            received += chunk.len();
+            assert!(parser.next_value().is_none());
+            // Once the string ran out of input, only the bytes of later chunks
+            // are scanned, without reading the string again from its start.
+            if index > 0 {
+                assert!(matches!(
+                    parser.lookahead,
+                    Some(Lookahead {
+                        position,
+                        state: Scan::String { .. },
+                    }) if position == received
+                ));
+            }
+        }
+        parser.feed(last);
+        assert_eq!(
+            parser.next_value().unwrap().unwrap(),
+            Value::Array(vec![Value::String(text.replace("\\u00fc", "ü"))])
+        );
+    }
+
+    #[test]
+    fn several_values() {
+        let input = br#"{"a":1}{"b":2} 3 "four"[5]"#;
+        let expected: Vec<_> = [r#"{"a":1}"#, r#"{"b":2}"#, "3", r#""four""#, "[5]"]
+            .into_iter()
+            .map(|json| Ok(parse(json).unwrap()))
+            .collect();
+        for size in 1..=input.len() {
+            assert_eq!(values_in_chunks(input, size), expected);
+        }
+    }
+
+    #[test_case(b"[1, 2" , vec![Err(ErrorKind::UnexpectedEndOfFile)] ; "Truncated array")]
+    #[test_case(b"\"abc", vec![Err(ErrorKind::InvalidToken)] ; "Truncated string")]
+    #[test_case(b"1 [} 2", vec![Ok("1"), Err(ErrorKind::UnexpectedToken)] ; "Stops at error")]
+    #[test_case(b"[\"\xC3(\"]", vec![Err(ErrorKind::InvalidEncoding)] ; "Invalid UTF-8")]
+    #[test_case(b"[\"\xC3", vec![Err(ErrorKind::InvalidEncoding)] ; "Truncated UTF-8")]
+    #[test_case(b"", vec![] ; "Empty input")]
+    fn errors(input: &[u8], expected: Vec<Result<&str, ErrorKind>>) {
+        let expected: Vec<_> = expected
+            .into_iter()
+            .map(|result| result.map(|json| parse(json).unwrap()))
+            .collect();
+        for size in 1..=input.len().max(1) {
+            assert_eq!(values_in_chunks(input, size), expected);
+        }
+    }
+
+    #[test]
+    fn values_as_soon_as_complete() {
+        let mut parser = IncrementalParser::new();
+        parser.feed(b"[1, 2");
+        assert!(parser.next_value().is_none());
+        parser.feed(b"]  {\"a\": tr");
+        assert_eq!(
+            parser.next_value().unwrap().unwrap(),
+            parse("[1, 2]").unwrap()
+        );
+        assert!(parser.next_value().is_none());
+        parser.feed(b"ue}");
+        assert_eq!(
+            parser.next_value().unwrap().unwrap(),
+            parse(r#"{"a": true}"#).unwrap()
+        );
+        parser.feed(b"12");
+        // The number may continue in the next chunk.
+        assert!(parser.next_value().is_none());
+        parser.finish();
+        assert_eq!(parser.next_value().unwrap().unwrap(), parse("12").unwrap());
+        assert!(parser.next_value().is_none());
+    }
+
+    #[test]
+    fn events_and_locations() {
+        let json = "{\n  \"a\": [\"x\", 1.5],\n  \"b\": null\n}";
+        let expected: Vec<_> = JsonEvents::new(json).collect::<Result<_, _>>().unwrap();
+        let mut parser = IncrementalParser::new();
+        let mut events = Vec::new();
+        for chunk in json.as_bytes().chunks(3) {
+            parser.feed(chunk);
+            events.extend(std::iter::from_fn(|| parser.next_event()));
+        }
+        parser.finish();
+        events.extend(std::iter::from_fn(|| parser.next_event()));
+        let events: Vec<_> = events.into_iter().collect::<Result<_, _>>().unwrap();
+        assert_eq!(events, expected);
+    }
+
+    #[test]
+    fn options() {
+        let mut parser = IncrementalParser::with_options(ParseOptions::from(Dialect::Jsonc));
+        for chunk in [&b"[1, /* com"[..], b"ment */ 2, // line\n", b"]"] {
+            parser.feed(chunk);
+        }
+        parser.finish();
+        assert_eq!(
+            parser.next_value().unwrap().unwrap(),
+            parse("[1, 2]").unwrap()
+        );
+    }
+}
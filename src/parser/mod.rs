@@ -1,9 +1,48 @@
+#[cfg(feature = "tokio")]
+mod async_io;
+mod cst;
+mod diagnostic;
+mod encoding;
+mod events;
+mod incremental;
+mod ndjson;
 mod number;
+mod options;
 mod parse_error;
+#[allow(clippy::module_inception)]
 mod parser;
+mod recovery;
+mod sequence;
+mod source;
+mod spanned;
+#[cfg(test)]
+mod test_readers;
 mod tokenizer;
 mod value;
+mod visitor;
+mod writer;
 
-pub use parse_error::ParseError;
-pub use parser::parse;
-pub use value::Value;
+#[cfg(feature = "tokio")]
+pub use async_io::{parse_async, parse_async_with, AsyncNdjsonReader};
+pub use cst::{Container, Document, EditError, Node};
+pub use diagnostic::Diagnostic;
+pub use encoding::Encoding;
+pub use events::{Event, JsonEvents, Scalar};
+pub use incremental::IncrementalParser;
+pub use ndjson::{NdjsonReader, NdjsonWriter};
+pub use number::{Number, NumberError};
+pub use options::{Dialect, DuplicateKeyPolicy, ParseOptions, DEFAULT_MAX_DEPTH};
+pub use parse_error::{ErrorKind, ParseError, ParseErrorArgs, TokenKind};
+pub use parser::{
+    parse, parse_borrowed, parse_borrowed_with, parse_bytes, parse_bytes_with, parse_prefix,
+    parse_reader, parse_reader_with, parse_spanned, parse_spanned_with, parse_with, visit,
+    visit_reader,
+};
+pub use recovery::{parse_recovering, parse_recovering_with};
+pub use sequence::ValueStream;
+pub use source::{ReadSource, Source, StrSource};
+pub use spanned::{Member, Position, Span, Spanned, SpannedBuilder, SpannedValue};
+pub use tokenizer::Location;
+pub use value::{BorrowedValue, Value};
+pub use visitor::{Tree, TreeBuilder, Visitor};
+pub use writer::{JsonWriter, WriteError};
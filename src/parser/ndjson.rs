@@ -0,0 +1,356 @@
+use super::{
+    encoding::{self, Encoding},
+    options::ParseOptions,
+    parse_error::ParseError,
+    parser::build_tree,
+    source::StrSource,
+    tokenizer::{Location, Tokenizer},
+    value::Value,
+};
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    mem,
+};
+
+/// Reads newline-delimited JSON (NDJSON, JSON Lines): one value per line,
+/// each parsed on its own. Blank lines are skipped. Error locations count
+/// lines and byte offsets from the start of the input, so they can be shown
+/// with [`ParseError::diagnostic`] against the whole input.
+///
+/// By default iteration ends after the first malformed line;
+/// [`skip_invalid`](NdjsonReader::skip_invalid) carries on past them. A line
+/// is only buffered up to the input size limit of the
+/// [`options`](NdjsonReader::options), beyond which it is malformed.
+pub struct NdjsonReader<R: BufRead> {
+    reader: R,
+    lines: Lines,
+}
+
+impl<R: BufRead> NdjsonReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            lines: Lines::default(),
+        }
+    }
+
+    /// Parse every line according to `options`.
+    pub fn options(mut self, options: impl Into<ParseOptions>) -> Self {
+        self.lines.options = options.into();
+        self
+    }
+
+    /// Skip malformed lines instead of stopping at them. Errors reading the
+    /// input still end the iteration.
+    pub fn skip_invalid(mut self, skip_invalid: bool) -> Self {
+        self.lines.skip_invalid = skip_invalid;
+        self
+    }
+}
+
+impl<R: Read> NdjsonReader<BufReader<R>> {
+    /// Reads from an unbuffered `reader`.
+    pub fn from_reader(reader: R) -> Self {
+        Self::new(BufReader::new(reader))
+    }
+}
+
+impl<R: BufRead> Iterator for NdjsonReader<R> {
+    type Item = Result<Value, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.lines.is_done {
+            let available = match self.reader.fill_buf() {
+                Ok(available) => available,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Some(self.lines.fail(error)),
+            };
+            let (length, result) = self.lines.read(available);
+            self.reader.consume(length);
+            if result.is_some() {
+                return result;
+            }
+        }
+        None
+    }
+}
+
+/// Options and position of a reader of newline-delimited JSON, independent
+/// of how the lines are read.
+#[derive(Default)]
+pub struct Lines {
+    pub options: ParseOptions,
+    pub skip_invalid: bool,
+    pub is_done: bool,
+    /// The part of the current line read so far.
+    line: Vec<u8>,
+    /// Whether the rest of the current line is discarded, as it is too long.
+    is_discarding: bool,
+    /// Number of lines read so far.
+    line_number: usize,
+    /// Byte offset of the next line.
+    offset: usize,
+}
+
+impl Lines {
+    /// Takes the current line up to its line break from `available`, the
+    /// buffered input, which is empty at the end of input. Returns the number
+    /// of bytes taken and the result of the line if they completed it,
+    /// `None` if it is skipped.
+    pub fn read(&mut self, available: &[u8]) -> (usize, Option<Result<Value, ParseError>>) {
+        if available.is_empty() {
+            // The last line may lack a line break.
+            self.is_done = true;
+            let is_pending = !self.line.is_empty() && !self.is_discarding;
+            return (0, if is_pending { self.parse_line() } else { None });
+        }
+        let end = available.iter().position(|byte| *byte == b'\n');
+        let length = end.map_or(available.len(), |index| index + 1);
+        if self.is_discarding {
+            self.offset += length;
+            self.is_discarding = end.is_none();
+            return (length, None);
+        }
+        self.line.extend_from_slice(&available[..length]);
+        let result = if end.is_some() {
+            self.parse_line()
+        } else if self.line.len() > self.options.max_input_size.saturating_add(1) {
+            // Even if a `\r` at the end belongs to the line break, the rest
+            // is over the limit.
+            self.too_long()
+        } else {
+            None
+        };
+        (length, result)
+    }
+
+    fn parse_line(&mut self) -> Option<Result<Value, ParseError>> {
+        let line = mem::take(&mut self.line);
+        let result = self.parse(&line);
+        self.line = line;
+        self.line.clear();
+        result
+    }
+
+    /// Starts discarding the current line, which grew beyond the input size
+    /// limit without a line break.
+    fn too_long(&mut self) -> Option<Result<Value, ParseError>> {
+        let limit = self.options.max_input_size;
+        let location = Location {
+            line: self.line_number + 1,
+            column: String::from_utf8_lossy(&self.line[..limit]).chars().count() + 1,
+            length: 1,
+            offset: self.offset + limit,
+        };
+        self.offset += self.line.len();
+        self.line_number += 1;
+        self.line.clear();
+        self.is_discarding = true;
+        if self.skip_invalid {
+            return None;
+        }
+        self.is_done = true;
+        Some(Err(ParseError::InputSizeLimitExceeded { location, limit }))
+    }
+
+    /// Parses the next `line`, including its line break. Returns `None` for
+    /// lines that are skipped.
+    fn parse(&mut self, line: &[u8]) -> Option<Result<Value, ParseError>> {
+        let offset = self.offset;
+        self.offset += line.len();
+        self.line_number += 1;
+        if line.trim_ascii().is_empty() {
+            return None;
+        }
+        let line = line.strip_suffix(b"\n").unwrap_or(line);
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let (text, is_complete) = encoding::decode_utf8(line);
+        let source = if is_complete {
+            StrSource::new(text)
+        } else {
+            StrSource::truncated(text, Encoding::Utf8)
+        };
+        match build_tree(&mut Tokenizer::from_source(source, self.options.clone())) {
+            Err(_) if self.skip_invalid => None,
+            Err(error) => {
+                self.is_done = true;
+                Some(Err(error.relocate(self.line_number - 1, offset)))
+            }
+            value => Some(value),
+        }
+    }
+
+    /// Ends reading after an I/O error.
+    pub fn fail(&mut self, error: io::Error) -> Result<Value, ParseError> {
+        self.is_done = true;
+        Err(ParseError::Io(error))
+    }
+}
+
+/// Writes values as newline-delimited JSON: each compactly on a line of its
+/// own.
+pub struct NdjsonWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> NdjsonWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    pub fn write(&mut self, value: &Value) -> io::Result<()> {
+        let line = value.stringified().compact(true).create();
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.write_all(b"\n")
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse, test_readers::FailingReader, Dialect, ErrorKind, Location};
+    use test_case::test_case;
+
+    fn read(input: &[u8], skip_invalid: bool) -> Vec<Result<Value, ErrorKind>> {
+        NdjsonReader::new(input)
+            .skip_invalid(skip_invalid)
+            .map(|result| result.map_err(|error| error.kind()))
+            .collect()
+    }
+
+    #[test_case(b"1\n\"a\"\n[true]\n", vec![Ok("1"), Ok("\"a\""), Ok("[true]")] ; "One value per line")]
+    #[test_case(b"{\"a\": 1}\r\n{\"a\": 2}", vec![Ok("{\"a\": 1}"), Ok("{\"a\": 2}")] ; "CRLF and no final newline")]
+    #[test_case(b"\n1\n  \n\t\n2\n\n", vec![Ok("1"), Ok("2")] ; "Blank lines")]
+    #[test_case(b"", vec![] ; "Empty input")]
+    #[test_case(b"1\n[1,\n2\n", vec![Ok("1"), Err(ErrorKind::UnexpectedEndOfFile)] ; "Stops at malformed line")]
+    #[test_case(b"1 2\n3\n", vec![Err(ErrorKind::TrailingContent)] ; "Two values on a line")]
+    #[test_case(b"\"\xC3(\"\n1\n", vec![Err(ErrorKind::InvalidEncoding)] ; "Invalid UTF-8")]
+    fn reader(input: &[u8], expected: Vec<Result<&str, ErrorKind>>) {
+        let expected: Vec<_> = expected
+            .into_iter()
+            .map(|result| result.map(|json| parse(json).unwrap()))
+            .collect();
+        assert_eq!(read(input, false), expected);
+    }
+
+    #[test]
+    fn skip_invalid() {
+        let input = b"1\n[1,\n{\"a\" 1}\n2\n";
+        let values = read(input, true);
+        assert_eq!(
+            values,
+            vec![Ok(parse("1").unwrap()), Ok(parse("2").unwrap())]
+        );
+    }
+
+    #[test]
+    fn error_location() {
+        let input = "1\r\n\n  {\"a\": nul}\n";
+        let error = NdjsonReader::from_reader(input.as_bytes())
+            .nth(1)
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(
+            error.location(),
+            Some(Location {
+                line: 3,
+                column: 9,
+                length: 3,
+                offset: 12,
+            })
+        );
+        assert!(error
+            .diagnostic(input)
+            .to_string()
+            .contains("3 |   {\"a\": nul}"));
+    }
+
+    #[test]
+    fn options() {
+        let input = b"{a: 1} // first\n[2,]\n";
+        let values: Vec<_> = NdjsonReader::new(&input[..])
+            .options(Dialect::Json5)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            values,
+            vec![parse(r#"{"a": 1}"#).unwrap(), parse("[2]").unwrap()]
+        );
+    }
+
+    #[test_case(4 ; "Line read in pieces")]
+    #[test_case(64 ; "Line read at once")]
+    fn line_too_long(capacity: usize) {
+        let options = ParseOptions::new().max_input_size(8);
+        let input = b"[1]\r\n[1, 2, 3, 4]\n[2, 3]\r\n";
+        let read = || BufReader::with_capacity(capacity, &input[..]);
+        let results: Vec<_> = NdjsonReader::new(read()).options(options.clone()).collect();
+        let [Ok(first), Err(error)] = &results[..] else {
+            panic!("Expected a value and an error, got {:?}", results);
+        };
+        assert_eq!(*first, parse("[1]").unwrap());
+        assert_eq!(error.kind(), ErrorKind::InputSizeLimitExceeded);
+        assert_eq!(
+            error
+                .location()
+                .map(|location| (location.line, location.column, location.offset)),
+            Some((2, 9, 13))
+        );
+        let values: Vec<_> = NdjsonReader::new(read())
+            .options(options)
+            .skip_invalid(true)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            values,
+            vec![parse("[1]").unwrap(), parse("[2, 3]").unwrap()]
+        );
+    }
+
+    #[test]
+    fn endless_line() {
+        let options = ParseOptions::new().max_input_size(1 << 16);
+        let mut reader = NdjsonReader::from_reader(io::repeat(b' ')).options(options);
+        let error = reader.next().unwrap().unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InputSizeLimitExceeded);
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn io_error() {
+        let mut reader = NdjsonReader::from_reader(FailingReader).skip_invalid(true);
+        assert!(matches!(reader.next(), Some(Err(ParseError::Io(_)))));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn writer() {
+        let values = [
+            parse(r#"{"a": [1, 2.5, null], "b": {"c": "line\nbreak"}}"#).unwrap(),
+            parse("[]").unwrap(),
+            parse("{}").unwrap(),
+            parse("\"text\"").unwrap(),
+        ];
+        let mut writer = NdjsonWriter::new(Vec::new());
+        for value in &values {
+            writer.write(value).unwrap();
+        }
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+        assert_eq!(
+            output,
+            "{\"a\":[1,2.5,null],\"b\":{\"c\":\"line\\nbreak\"}}\n[]\n{}\n\"text\"\n"
+        );
+        let read: Vec<_> = NdjsonReader::new(output.as_bytes())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(read, values);
+    }
+}
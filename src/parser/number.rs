@@ -1,20 +1,25 @@
-#[derive(Debug, PartialEq, PartialOrd)]
+use std::{borrow::Cow, fmt::Display};
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub enum Number {
     Integer(i64),
     UnsingedInteger(u64),
     Float(f64),
+    /// The number exactly as written in the source, produced when parsing with
+    /// [`ParseOptions::lossless_numbers`](super::ParseOptions::lossless_numbers).
+    Raw(String),
 }
 
 impl Number {
-    pub fn parse(sequence: &String) -> Option<Self> {
+    pub fn parse(sequence: &str) -> Option<Self> {
         if let Ok(integer) = sequence.parse::<u64>() {
             Some(Number::UnsingedInteger(integer))
         } else if let Ok(integer) = sequence.parse::<i64>() {
             Some(Number::Integer(integer))
         } else if let Ok(float) = sequence.parse::<f64>() {
-            if float.fract() == 0.0 && float >= u64::MIN as f64 && float <= u64::MAX as f64 {
+            if float.fract() == 0.0 && (u64::MIN as f64..=u64::MAX as f64).contains(&float) {
                 Some(Number::UnsingedInteger(float as u64))
-            } else if float.fract() == 0.0 && float >= i64::MIN as f64 && float <= i64::MAX as f64 {
+            } else if float.fract() == 0.0 && (i64::MIN as f64..=i64::MAX as f64).contains(&float) {
                 Some(Number::Integer(float as i64))
             } else {
                 Some(Number::Float(float))
@@ -24,11 +29,126 @@ impl Number {
         }
     }
 
-    pub fn to_string(&self) -> String {
+    /// Returns the number as an `i128` if it is an integer in range, no matter
+    /// whether it was written with a fraction or an exponent.
+    pub fn as_i128(&self) -> Option<i128> {
+        match self {
+            Self::Integer(integer) => Some(*integer as i128),
+            Self::UnsingedInteger(integer) => Some(*integer as i128),
+            Self::Float(float) => {
+                let in_range = (i128::MIN as f64..=i128::MAX as f64).contains(float);
+                (float.fract() == 0.0 && in_range).then_some(*float as i128)
+            }
+            Self::Raw(lexeme) => raw_as_i128(lexeme),
+        }
+    }
+
+    /// Returns the nearest `f64`, which may lose precision or be infinite.
+    pub fn as_f64_lossy(&self) -> f64 {
         match self {
-            Self::Integer(integer) => integer.to_string(),
-            Self::UnsingedInteger(integer) => integer.to_string(),
-            Self::Float(float) => float.to_string(),
+            Self::Integer(integer) => *integer as f64,
+            Self::UnsingedInteger(integer) => *integer as f64,
+            Self::Float(float) => *float,
+            Self::Raw(lexeme) => lexeme.parse().unwrap_or(f64::NAN),
         }
     }
+
+    /// Returns the number in decimal notation, borrowing the original lexeme
+    /// of raw numbers.
+    pub fn as_decimal_str(&self) -> Cow<'_, str> {
+        match self {
+            Self::Raw(lexeme) => Cow::Borrowed(lexeme),
+            _ => Cow::Owned(self.to_string()),
+        }
+    }
+}
+
+/// Converts a lexeme matching the JSON number grammar to an `i128` by exactly
+/// shifting its significant digits by the exponent.
+fn raw_as_i128(lexeme: &str) -> Option<i128> {
+    let (negative, unsigned) = match lexeme.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, lexeme),
+    };
+    let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+        Some(idx) => (&unsigned[..idx], &unsigned[idx + 1..]),
+        None => (unsigned, "0"),
+    };
+    let (integer_part, fraction_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{}{}", integer_part, fraction_part);
+    let digits = digits.trim_start_matches('0');
+    let significant = digits.trim_end_matches('0');
+    if significant.is_empty() {
+        return Some(0);
+    }
+    let exponent = exponent
+        .parse::<i64>()
+        .ok()?
+        .saturating_sub(fraction_part.len() as i64)
+        .saturating_add((digits.len() - significant.len()) as i64);
+    let zeros = usize::try_from(exponent).ok()?;
+    if significant.len() + zeros > 39 {
+        return None;
+    }
+    let sign = if negative { "-" } else { "" };
+    format!("{}{}{}", sign, significant, "0".repeat(zeros))
+        .parse()
+        .ok()
+}
+
+impl Display for Number {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Integer(integer) => write!(f, "{}", integer),
+            Self::UnsingedInteger(integer) => write!(f, "{}", integer),
+            Self::Float(float) => write!(f, "{}", float),
+            Self::Raw(lexeme) => write!(f, "{}", lexeme),
+        }
+    }
+}
+
+/// Violations of the number grammar of the active dialect detected by the
+/// tokenizer.
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy)]
+pub enum NumberError {
+    LeadingPlus,
+    LeadingZero,
+    MissingIntegerDigits,
+    MissingFractionDigits,
+    MissingExponentDigits,
+    MissingHexDigits,
+}
+
+impl Display for NumberError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            Self::LeadingPlus => "leading `+` not allowed",
+            Self::LeadingZero => "leading zero not allowed",
+            Self::MissingIntegerDigits => "integer part must start with a digit",
+            Self::MissingFractionDigits => "digit expected after decimal point",
+            Self::MissingExponentDigits => "digit expected in exponent",
+            Self::MissingHexDigits => "hexadecimal digit expected after `0x`",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl std::error::Error for NumberError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("0.22e2", Some(22) ; "Fraction shifted by exponent")]
+    #[test_case("-1500e-2", Some(-15) ; "Negative exponent with trailing zeros")]
+    #[test_case("170141183460469231731687303715884105727", Some(i128::MAX) ; "Maximum")]
+    #[test_case("170141183460469231731687303715884105728", None ; "Overflow")]
+    #[test_case("1.5", None ; "Fraction")]
+    #[test_case("1e99999999999999999999", None ; "Exponent overflowing i64")]
+    #[test_case("0e99999999999999999999", Some(0) ; "Zero with huge exponent")]
+    #[test_case("0.000", Some(0) ; "Zero with fraction")]
+    fn raw_as_i128(lexeme: &str, expected: Option<i128>) {
+        assert_eq!(Number::Raw(lexeme.to_string()).as_i128(), expected);
+    }
 }
@@ -0,0 +1,148 @@
+/// The syntax accepted on top of RFC 8259 JSON.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Dialect {
+    /// Strict JSON.
+    #[default]
+    Json,
+    /// JSON with `//` and `/* */` comments and trailing commas, as used by
+    /// VS Code configuration files.
+    Jsonc,
+    /// [JSON5](https://spec.json5.org): JSONC plus identifier keys,
+    /// single-quoted strings with additional escapes and line continuations,
+    /// hexadecimal numbers, `Infinity`, `NaN`, leading or trailing decimal
+    /// points and an explicit `+`. Numbers are normalized to their JSON form,
+    /// so lossless numbers hold e.g. `0.5` for `+.5`, while hexadecimal and
+    /// non-finite numbers are always converted.
+    Json5,
+}
+
+impl Dialect {
+    pub(crate) fn allows_comments(self) -> bool {
+        self != Dialect::Json
+    }
+
+    pub(crate) fn allows_trailing_commas(self) -> bool {
+        self != Dialect::Json
+    }
+}
+
+impl From<Dialect> for ParseOptions {
+    fn from(dialect: Dialect) -> Self {
+        Self::new().dialect(dialect)
+    }
+}
+
+/// How objects treat a key that occurs more than once.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum DuplicateKeyPolicy {
+    /// Fail with [`ParseError::DuplicateKey`](super::ParseError::DuplicateKey).
+    Error,
+    /// Keep the first value and ignore later ones.
+    FirstWins,
+    /// Keep the last value at the position of the first occurrence.
+    #[default]
+    LastWins,
+    /// Gather all values of the key into an array.
+    Collect,
+}
+
+/// Settings for [`parse_with`](super::parse_with). The defaults match
+/// [`parse`](super::parse): apart from the nesting depth, no limits apply.
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    pub(crate) dialect: Dialect,
+    pub(crate) lossless_numbers: bool,
+    pub(crate) replace_lone_surrogates: bool,
+    pub(crate) duplicate_keys: DuplicateKeyPolicy,
+    pub(crate) max_depth: usize,
+    pub(crate) max_input_size: usize,
+    pub(crate) max_string_length: usize,
+    pub(crate) max_members: usize,
+    pub(crate) max_values: usize,
+}
+
+/// Nesting depth allowed by default, deep enough for any sensible document
+/// while keeping the recursive parser far away from a stack overflow.
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            dialect: Dialect::default(),
+            lossless_numbers: false,
+            replace_lone_surrogates: false,
+            duplicate_keys: DuplicateKeyPolicy::default(),
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_input_size: usize::MAX,
+            max_string_length: usize::MAX,
+            max_members: usize::MAX,
+            max_values: usize::MAX,
+        }
+    }
+}
+
+impl ParseOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Keep every number as [`Number::Raw`](super::Number::Raw) holding the
+    /// original lexeme instead of converting it to `i64`, `u64` or `f64`.
+    pub fn lossless_numbers(mut self, lossless_numbers: bool) -> Self {
+        self.lossless_numbers = lossless_numbers;
+        self
+    }
+
+    /// Replace `\u` escapes of unpaired UTF-16 surrogates with U+FFFD instead
+    /// of failing with [`ParseError::LoneSurrogate`](super::ParseError::LoneSurrogate).
+    pub fn replace_lone_surrogates(mut self, replace_lone_surrogates: bool) -> Self {
+        self.replace_lone_surrogates = replace_lone_surrogates;
+        self
+    }
+
+    /// Policy for keys occurring more than once in an object. Except for
+    /// [`DuplicateKeyPolicy::Error`], policies are applied when building a
+    /// [`Value`](super::Value), whereas visitors and events see every property.
+    pub fn duplicate_keys(mut self, duplicate_keys: DuplicateKeyPolicy) -> Self {
+        self.duplicate_keys = duplicate_keys;
+        self
+    }
+
+    /// Maximum number of nested objects and arrays. Exceeding it fails with
+    /// [`ParseError::DepthLimitExceeded`](super::ParseError::DepthLimitExceeded).
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Maximum size of the input in bytes, checked while reading so that
+    /// oversized streams are aborted early.
+    pub fn max_input_size(mut self, max_input_size: usize) -> Self {
+        self.max_input_size = max_input_size;
+        self
+    }
+
+    /// Maximum length in bytes of a decoded string or object key.
+    pub fn max_string_length(mut self, max_string_length: usize) -> Self {
+        self.max_string_length = max_string_length;
+        self
+    }
+
+    /// Maximum number of properties in an object or elements in an array.
+    pub fn max_members(mut self, max_members: usize) -> Self {
+        self.max_members = max_members;
+        self
+    }
+
+    /// Maximum number of values in the whole document, counting every
+    /// object, array and scalar.
+    pub fn max_values(mut self, max_values: usize) -> Self {
+        self.max_values = max_values;
+        self
+    }
+}
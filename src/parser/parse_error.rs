@@ -1,67 +1,393 @@
-use super::tokenizer::{Location, Token};
-use std::fmt::Display;
+use super::{
+    encoding::Encoding,
+    number::NumberError,
+    tokenizer::{Location, Token},
+};
+use std::{fmt::Display, io};
+
+/// The kinds of tokens a [`ParseError`] reports as expected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    Null,
+    True,
+    False,
+    Number,
+    String,
+    /// An unquoted JSON5 key.
+    Identifier,
+    BeginObject,
+    EndObject,
+    BeginArray,
+    EndArray,
+    NameSeparator,
+    ValueSeparator,
+    /// The `0x1E` starting a record of an RFC 7464 JSON text sequence.
+    RecordSeparator,
+}
+
+impl TokenKind {
+    /// Everything a value may start with.
+    pub(crate) const VALUE: [TokenKind; 7] = [
+        Self::Null,
+        Self::True,
+        Self::False,
+        Self::Number,
+        Self::String,
+        Self::BeginObject,
+        Self::BeginArray,
+    ];
+}
+
+impl Display for TokenKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let token = match self {
+            Self::Null => "null",
+            Self::True => "true",
+            Self::False => "false",
+            Self::Number => "number",
+            Self::String => "string",
+            Self::Identifier => "identifier",
+            Self::BeginObject => "{",
+            Self::EndObject => "}",
+            Self::BeginArray => "[",
+            Self::EndArray => "]",
+            Self::NameSeparator => ":",
+            Self::ValueSeparator => ",",
+            Self::RecordSeparator => "record separator",
+        };
+        write!(f, "{}", token)
+    }
+}
 
 #[derive(Debug)]
 pub struct ParseErrorArgs {
-    token: Token,
-    expected_tokens: Vec<String>,
+    found: String,
+    location: Location,
+    expected_tokens: Vec<TokenKind>,
 }
 
 impl ParseErrorArgs {
-    pub fn new(token: Token, expected_tokens: Vec<&str>) -> Self {
-        let expected_tokens = expected_tokens
-            .iter()
-            .map(|s| s.to_string())
-            .collect::<Vec<String>>();
+    pub(crate) fn new(token: Token, expected_tokens: Vec<TokenKind>) -> Self {
         Self {
-            token,
+            found: token.value.into_owned(),
+            location: token.location,
             expected_tokens,
         }
     }
+
+    /// The tokens that would have been valid instead.
+    pub fn expected(&self) -> &[TokenKind] {
+        &self.expected_tokens
+    }
+
+    /// The source text of the offending token.
+    pub fn found(&self) -> &str {
+        &self.found
+    }
+
+    pub fn location(&self) -> Location {
+        self.location
+    }
+}
+
+/// What went wrong, without the details carried by the [`ParseError`]
+/// variants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    UnexpectedEndOfFile,
+    UnterminatedComment,
+    UnexpectedToken,
+    InvalidToken,
+    TrailingContent,
+    InvalidNumber,
+    ControlCharacterInString,
+    LoneSurrogate,
+    InvalidEscape,
+    Io,
+    InvalidEncoding,
+    DepthLimitExceeded,
+    InputSizeLimitExceeded,
+    StringLengthLimitExceeded,
+    MemberLimitExceeded,
+    ValueLimitExceeded,
+    DuplicateKey,
+    Rejected,
 }
 
 #[derive(Debug)]
 pub enum ParseError {
     UnexpectedEndOfFile(Location),
+    /// A block comment without closing `*/`, located at its opening `/*`.
+    UnterminatedComment(Location),
     UnexpectedToken(ParseErrorArgs),
     InvalidToken(ParseErrorArgs),
+    /// A token after the top-level value, with its source text.
+    TrailingContent {
+        found: String,
+        location: Location,
+    },
+    InvalidNumber {
+        value: String,
+        error: NumberError,
+        location: Location,
+    },
+    /// A raw control character in a string, which JSON requires to be escaped.
+    ControlCharacterInString {
+        character: char,
+        location: Location,
+    },
+    /// A `\u` escape of a UTF-16 surrogate without its other half, unless
+    /// [`ParseOptions::replace_lone_surrogates`](super::ParseOptions::replace_lone_surrogates)
+    /// is set.
+    LoneSurrogate {
+        code_unit: u16,
+        location: Location,
+    },
+    InvalidEscape {
+        sequence: String,
+        location: Location,
+    },
+    Io(io::Error),
+    /// Input that is not valid in its encoding, located at the first
+    /// offending byte.
+    InvalidEncoding {
+        encoding: Encoding,
+        location: Location,
+    },
+    DepthLimitExceeded {
+        location: Location,
+        limit: usize,
+    },
+    InputSizeLimitExceeded {
+        location: Location,
+        limit: usize,
+    },
+    StringLengthLimitExceeded {
+        location: Location,
+        limit: usize,
+    },
+    MemberLimitExceeded {
+        location: Location,
+        limit: usize,
+    },
+    ValueLimitExceeded {
+        location: Location,
+        limit: usize,
+    },
+    DuplicateKey {
+        key: String,
+        original: Location,
+        duplicate: Location,
+    },
+    /// A [`Visitor`](super::Visitor) callback returned `error` for the token
+    /// at `location`.
+    Rejected {
+        error: Box<dyn std::error::Error + Send + Sync>,
+        location: Location,
+    },
 }
 
-impl Display for ParseError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl ParseError {
+    pub(crate) fn trailing_content(token: Token) -> Self {
+        Self::TrailingContent {
+            found: token.value.into_owned(),
+            location: token.location,
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::UnexpectedEndOfFile(_) => ErrorKind::UnexpectedEndOfFile,
+            Self::UnterminatedComment(_) => ErrorKind::UnterminatedComment,
+            Self::UnexpectedToken(_) => ErrorKind::UnexpectedToken,
+            Self::InvalidToken(_) => ErrorKind::InvalidToken,
+            Self::TrailingContent { .. } => ErrorKind::TrailingContent,
+            Self::InvalidNumber { .. } => ErrorKind::InvalidNumber,
+            Self::ControlCharacterInString { .. } => ErrorKind::ControlCharacterInString,
+            Self::LoneSurrogate { .. } => ErrorKind::LoneSurrogate,
+            Self::InvalidEscape { .. } => ErrorKind::InvalidEscape,
+            Self::Io(_) => ErrorKind::Io,
+            Self::InvalidEncoding { .. } => ErrorKind::InvalidEncoding,
+            Self::DepthLimitExceeded { .. } => ErrorKind::DepthLimitExceeded,
+            Self::InputSizeLimitExceeded { .. } => ErrorKind::InputSizeLimitExceeded,
+            Self::StringLengthLimitExceeded { .. } => ErrorKind::StringLengthLimitExceeded,
+            Self::MemberLimitExceeded { .. } => ErrorKind::MemberLimitExceeded,
+            Self::ValueLimitExceeded { .. } => ErrorKind::ValueLimitExceeded,
+            Self::DuplicateKey { .. } => ErrorKind::DuplicateKey,
+            Self::Rejected { .. } => ErrorKind::Rejected,
+        }
+    }
+
+    /// Where the error occurred, `None` for I/O errors. Duplicate keys are
+    /// located at the repetition.
+    pub fn location(&self) -> Option<Location> {
+        match *self {
+            Self::UnexpectedEndOfFile(location)
+            | Self::UnterminatedComment(location)
+            | Self::InvalidNumber { location, .. }
+            | Self::ControlCharacterInString { location, .. }
+            | Self::LoneSurrogate { location, .. }
+            | Self::InvalidEscape { location, .. }
+            | Self::InvalidEncoding { location, .. }
+            | Self::DepthLimitExceeded { location, .. }
+            | Self::InputSizeLimitExceeded { location, .. }
+            | Self::StringLengthLimitExceeded { location, .. }
+            | Self::MemberLimitExceeded { location, .. }
+            | Self::ValueLimitExceeded { location, .. }
+            | Self::Rejected { location, .. }
+            | Self::TrailingContent { location, .. }
+            | Self::DuplicateKey {
+                duplicate: location,
+                ..
+            } => Some(location),
+            Self::UnexpectedToken(ref args) | Self::InvalidToken(ref args) => Some(args.location()),
+            Self::Io(_) => None,
+        }
+    }
+
+    /// The tokens that would have been valid, empty unless the error is
+    /// about an unexpected or invalid token.
+    pub fn expected(&self) -> &[TokenKind] {
         match self {
-            Self::UnexpectedEndOfFile(location) => {
-                write!(f, "{}: Unexpected end of file", format_location(&location))
+            Self::UnexpectedToken(args) | Self::InvalidToken(args) => args.expected(),
+            _ => &[],
+        }
+    }
+
+    /// The source text of the offending token, if the error is about one.
+    pub fn found(&self) -> Option<&str> {
+        match self {
+            Self::UnexpectedToken(args) | Self::InvalidToken(args) => Some(args.found()),
+            Self::TrailingContent { found, .. } => Some(found),
+            Self::InvalidNumber { value, .. } => Some(value),
+            Self::InvalidEscape { sequence, .. } => Some(sequence),
+            _ => None,
+        }
+    }
+
+    /// Moves every location `lines` lines and `offset` bytes further, for
+    /// errors in a fragment of input that started there.
+    pub(crate) fn relocate(mut self, lines: usize, offset: usize) -> Self {
+        let shift = |location: &mut Location| {
+            location.line += lines;
+            location.offset += offset;
+        };
+        match &mut self {
+            Self::UnexpectedEndOfFile(location)
+            | Self::UnterminatedComment(location)
+            | Self::InvalidNumber { location, .. }
+            | Self::ControlCharacterInString { location, .. }
+            | Self::LoneSurrogate { location, .. }
+            | Self::InvalidEscape { location, .. }
+            | Self::InvalidEncoding { location, .. }
+            | Self::DepthLimitExceeded { location, .. }
+            | Self::InputSizeLimitExceeded { location, .. }
+            | Self::StringLengthLimitExceeded { location, .. }
+            | Self::MemberLimitExceeded { location, .. }
+            | Self::ValueLimitExceeded { location, .. }
+            | Self::Rejected { location, .. }
+            | Self::TrailingContent { location, .. } => shift(location),
+            Self::UnexpectedToken(args) | Self::InvalidToken(args) => shift(&mut args.location),
+            Self::DuplicateKey {
+                original,
+                duplicate,
+                ..
+            } => {
+                shift(original);
+                shift(duplicate);
             }
-            Self::UnexpectedToken(args) => {
-                write!(
-                    f,
-                    "{}: {}. Received token `{}`",
-                    format_location(&args.token.location),
-                    format_expected(&args.expected_tokens),
-                    args.token.value
-                )
+            Self::Io(_) => {}
+        }
+        self
+    }
+
+    /// The error message without its location.
+    pub(crate) fn message(&self) -> String {
+        match self {
+            Self::UnexpectedEndOfFile(_) => "Unexpected end of file".to_string(),
+            Self::UnterminatedComment(_) => "Unterminated block comment, expected `*/`".to_string(),
+            Self::UnexpectedToken(args) => format!(
+                "{}. Received token `{}`",
+                format_expected(&args.expected_tokens),
+                args.found
+            ),
+            Self::InvalidToken(args) => format!(
+                "{}. Received invalid token `{}`",
+                format_expected(&args.expected_tokens),
+                args.found
+            ),
+            Self::TrailingContent { found, .. } => {
+                format!("Expected end of file. Received trailing token `{}`", found)
+            }
+            Self::InvalidNumber { value, error, .. } => {
+                format!("Invalid number `{}`, {}", value, error)
+            }
+            Self::ControlCharacterInString { character, .. } => format!(
+                "Unescaped control character U+{:04X} in string",
+                *character as u32
+            ),
+            Self::LoneSurrogate { code_unit, .. } => {
+                format!("Unpaired surrogate `\\u{:04X}` in string", code_unit)
             }
-            Self::InvalidToken(args) => {
-                write!(
-                    f,
-                    "{}: {}. Received invalid token `{}`",
-                    format_location(&args.token.location),
-                    format_expected(&args.expected_tokens),
-                    args.token.value
+            Self::InvalidEscape { sequence, .. } => {
+                format!("Invalid escape sequence `{}` in string", sequence)
+            }
+            Self::Io(error) => error.to_string(),
+            Self::InvalidEncoding { encoding, .. } => format!("Invalid {} sequence", encoding),
+            Self::DepthLimitExceeded { limit, .. } => {
+                format!("Exceeded the maximum nesting depth of {}", limit)
+            }
+            Self::InputSizeLimitExceeded { limit, .. } => {
+                format!("Exceeded the maximum input size of {} bytes", limit)
+            }
+            Self::StringLengthLimitExceeded { limit, .. } => {
+                format!("Exceeded the maximum string length of {} bytes", limit)
+            }
+            Self::MemberLimitExceeded { limit, .. } => {
+                format!(
+                    "Exceeded the maximum of {} members per object or array",
+                    limit
                 )
             }
+            Self::ValueLimitExceeded { limit, .. } => {
+                format!("Exceeded the maximum of {} values per document", limit)
+            }
+            Self::DuplicateKey { key, original, .. } => format!(
+                "Duplicate key `{}`, first defined on line {} column {}",
+                key, original.line, original.column
+            ),
+            Self::Rejected { error, .. } => error.to_string(),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.location() {
+            Some(location) => write!(f, "{}: {}", format_location(&location), self.message()),
+            None => write!(f, ">> Error reading input: {}", self.message()),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::InvalidNumber { error, .. } => Some(error),
+            Self::Rejected { error, .. } => Some(error.as_ref()),
+            _ => None,
         }
     }
 }
 
-fn format_expected(expected_tokens: &Vec<String>) -> String {
+fn format_expected(expected_tokens: &[TokenKind]) -> String {
     let expected_string =
         expected_tokens
             .iter()
             .enumerate()
             .fold(String::new(), |mut s, (idx, token)| {
-                if idx + 1 == expected_tokens.len() {
+                if idx > 0 && idx + 1 == expected_tokens.len() {
                     s.push_str(" or ")
                 } else if idx > 0 {
                     s.push_str(", ")
@@ -78,3 +404,91 @@ fn format_location(location: &Location) -> String {
         location.line, location.column
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::test_readers::FailingReader;
+    use crate::parser::{parse, parse_reader, parse_with, DuplicateKeyPolicy, ParseOptions};
+    use std::error::Error;
+    use test_case::test_case;
+
+    #[test_case("[1", ErrorKind::UnexpectedEndOfFile, (1, 3, 2) ; "End of file")]
+    #[test_case("[1 2]", ErrorKind::UnexpectedToken, (1, 4, 3) ; "Unexpected token")]
+    #[test_case("[tru]", ErrorKind::InvalidToken, (1, 2, 1) ; "Invalid token")]
+    #[test_case("{} {}", ErrorKind::TrailingContent, (1, 4, 3) ; "Trailing content")]
+    #[test_case("\"ü\", 01", ErrorKind::TrailingContent, (1, 4, 4) ; "Offset counts bytes")]
+    #[test_case("[\n01]", ErrorKind::InvalidNumber, (2, 1, 2) ; "Invalid number")]
+    fn kind_and_location(
+        json: &str,
+        kind: ErrorKind,
+        (line, column, offset): (usize, usize, usize),
+    ) {
+        let error = parse(json).unwrap_err();
+        assert_eq!(error.kind(), kind);
+        let location = error.location().unwrap();
+        assert_eq!(
+            (location.line, location.column, location.offset),
+            (line, column, offset)
+        );
+    }
+
+    #[test]
+    fn expected_and_found() {
+        let error = parse(r#"{"a": 1 "b": 2}"#).unwrap_err();
+        assert_eq!(
+            error.expected(),
+            [TokenKind::EndObject, TokenKind::ValueSeparator]
+        );
+        assert_eq!(error.found(), Some(r#""b""#));
+        let ParseError::UnexpectedToken(args) = &error else {
+            panic!("Expected unexpected token error");
+        };
+        assert_eq!(args.expected(), error.expected());
+        assert_eq!(args.location(), error.location().unwrap());
+
+        let error = parse("[").unwrap_err();
+        assert!(error.expected().is_empty());
+        assert_eq!(error.found(), None);
+    }
+
+    #[test]
+    fn expected_values() {
+        let error = parse("[:]").unwrap_err();
+        assert_eq!(error.expected(), TokenKind::VALUE);
+        assert_eq!(
+            error.to_string(),
+            ">> Parsing Error on line 1 column 2: Expected `null`, `true`, `false`, `number`, `string`, `{` or `[`. Received token `:`"
+        );
+    }
+
+    #[test]
+    fn duplicate_key_location() {
+        let options = ParseOptions::new().duplicate_keys(DuplicateKeyPolicy::Error);
+        let error = parse_with("{\"a\": 1,\n \"a\": 2}", options).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::DuplicateKey);
+        assert_eq!(error.location().unwrap().line, 2);
+    }
+
+    #[test]
+    fn error_source() {
+        let error = parse_reader(FailingReader).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Io);
+        assert_eq!(error.location(), None);
+        assert_eq!(error.source().unwrap().to_string(), "connection reset");
+
+        let error = parse("-01").unwrap_err();
+        let source = error.source().unwrap().downcast_ref::<NumberError>();
+        assert_eq!(source, Some(&NumberError::LeadingZero));
+    }
+
+    #[test]
+    fn question_mark() {
+        fn stringified_length(json: &str) -> Result<usize, Box<dyn Error>> {
+            let value = parse(json)?;
+            Ok(value.stringified().create().len())
+        }
+        let error = stringified_length("[1,").unwrap_err();
+        assert!(error.downcast_ref::<ParseError>().is_some());
+    }
+}
@@ -1,82 +1,316 @@
 use super::{
-    parse_error::{ParseError, ParseErrorArgs},
-    tokenizer::{Token, TokenType, Tokenizer, Location},
-    value::Value,
+    encoding,
+    options::{Dialect, DuplicateKeyPolicy, ParseOptions},
+    parse_error::{ParseError, ParseErrorArgs, TokenKind},
+    source::{Source, StrSource},
+    spanned::{Position, Span, Spanned, SpannedBuilder, SpannedValue},
+    tokenizer::{Location, StringError, Token, TokenType, Tokenizer},
+    value::{BorrowedValue, Value},
+    visitor::{Tree, TreeBuilder, Visitor},
 };
-use indexmap::map::IndexMap;
+use std::{borrow::Cow, collections::HashMap, error::Error, io::Read};
 
+/// Parses `json` as a single JSON text. Anything but whitespace following the
+/// top-level value is reported as [`ParseError::TrailingContent`].
 pub fn parse(json: &str) -> Result<Value, ParseError> {
-    let mut tokenizer = Tokenizer::new(json.to_string());
-    parse_value(&mut tokenizer, None)
+    parse_with(json, ParseOptions::default())
 }
 
-fn parse_value(tokenizer: &mut Tokenizer, token: Option<Token>) -> Result<Value, ParseError> {
+/// Like [`parse`], but configured by `options`.
+pub fn parse_with(json: &str, options: impl Into<ParseOptions>) -> Result<Value, ParseError> {
+    build_tree(&mut Tokenizer::with_options(json, options.into()))
+}
+
+/// Like [`parse`], but strings without escape sequences borrow from `json`
+/// instead of being copied.
+pub fn parse_borrowed(json: &str) -> Result<BorrowedValue<'_>, ParseError> {
+    parse_borrowed_with(json, ParseOptions::default())
+}
+
+/// Like [`parse_borrowed`], but configured by `options`.
+pub fn parse_borrowed_with(
+    json: &str,
+    options: impl Into<ParseOptions>,
+) -> Result<BorrowedValue<'_>, ParseError> {
+    build_tree(&mut Tokenizer::with_options(json, options.into()))
+}
+
+/// Like [`parse`], but pulls the input from `reader` in chunks instead of
+/// requiring it in memory as a whole.
+pub fn parse_reader<R: Read>(reader: R) -> Result<Value, ParseError> {
+    parse_reader_with(reader, ParseOptions::default())
+}
+
+/// Like [`parse_reader`], but configured by `options`.
+pub fn parse_reader_with<R: Read>(
+    reader: R,
+    options: impl Into<ParseOptions>,
+) -> Result<Value, ParseError> {
+    build_tree(&mut Tokenizer::from_reader(reader, options.into()))
+}
+
+/// Like [`parse`], but for raw bytes in any encoding detected by
+/// [`Encoding::detect`](super::Encoding::detect). UTF-16 and UTF-32 input is transcoded to UTF-8
+/// first, so locations of errors refer to the transcoded text. Invalid byte
+/// sequences are reported as [`ParseError::InvalidEncoding`] where parsing
+/// reaches them.
+pub fn parse_bytes(bytes: &[u8]) -> Result<Value, ParseError> {
+    parse_bytes_with(bytes, ParseOptions::default())
+}
+
+/// Like [`parse_bytes`], but configured by `options`.
+pub fn parse_bytes_with(
+    bytes: &[u8],
+    options: impl Into<ParseOptions>,
+) -> Result<Value, ParseError> {
+    let (text, error) = encoding::decode(bytes);
+    let source = match error {
+        Some((encoding, _)) => StrSource::truncated(&text, encoding),
+        None => StrSource::new(&text),
+    };
+    build_tree(&mut Tokenizer::from_source(source, options.into()))
+}
+
+/// Like [`parse`], but every value and object key carries the [`Span`] of
+/// input it was parsed from.
+pub fn parse_spanned(json: &str) -> Result<Spanned<SpannedValue>, ParseError> {
+    parse_spanned_with(json, ParseOptions::default())
+}
+
+/// Like [`parse_spanned`], but configured by `options`.
+pub fn parse_spanned_with(
+    json: &str,
+    options: impl Into<ParseOptions>,
+) -> Result<Spanned<SpannedValue>, ParseError> {
+    let mut tokenizer = Tokenizer::with_options(json, options.into());
+    let mut builder = SpannedBuilder::new(tokenizer.options().duplicate_keys);
+    parse_document(&mut tokenizer, &mut builder)?;
+    Ok(builder.finish().expect("a value was parsed"))
+}
+
+/// Parses `json` and reports its contents to `visitor` instead of building a
+/// [`Value`]. An error returned by `visitor` ends parsing as
+/// [`ParseError::Rejected`].
+pub fn visit<'a, V: Visitor<'a>>(
+    json: &'a str,
+    options: ParseOptions,
+    visitor: &mut V,
+) -> Result<(), ParseError> {
+    parse_document(&mut Tokenizer::with_options(json, options), visitor)
+}
+
+/// Like [`visit`], but pulls the input from `reader`.
+pub fn visit_reader<R: Read, V: Visitor<'static>>(
+    reader: R,
+    options: ParseOptions,
+    visitor: &mut V,
+) -> Result<(), ParseError> {
+    parse_document(&mut Tokenizer::from_reader(reader, options), visitor)
+}
+
+/// Parses the JSON value at the start of `json` and returns it together with
+/// the byte offset right after it. Whatever follows is left untouched, which
+/// allows extracting JSON embedded in a larger document.
+pub fn parse_prefix(json: &str) -> Result<(Value, usize), ParseError> {
+    let mut tokenizer = Tokenizer::new(json);
+    let mut builder = TreeBuilder::with_duplicate_keys(tokenizer.options().duplicate_keys);
+    parse_value(&mut tokenizer, None, &mut builder, &mut Counters::default())?;
+    let value = builder.finish().expect("a value was parsed");
+    Ok((value, tokenizer.offset()))
+}
+
+/// Parses the next of several values following one another in the input
+/// and returns it together with the byte offset it starts at, or `None` at
+/// the end of input.
+pub fn next_value<'a, S: Source<'a>, T: Tree<'a>>(
+    tokenizer: &mut Tokenizer<'a, S>,
+) -> Result<Option<(T, usize)>, ParseError> {
+    let Some(token) = next_token(tokenizer)? else {
+        return Ok(None);
+    };
+    let offset = token.location.offset;
+    let mut builder = TreeBuilder::with_duplicate_keys(tokenizer.options().duplicate_keys);
+    parse_value(
+        tokenizer,
+        Some(token),
+        &mut builder,
+        &mut Counters::default(),
+    )?;
+    Ok(Some((
+        builder.finish().expect("a value was parsed"),
+        offset,
+    )))
+}
+
+pub fn build_tree<'a, S: Source<'a>, T: Tree<'a>>(
+    tokenizer: &mut Tokenizer<'a, S>,
+) -> Result<T, ParseError> {
+    let mut builder = TreeBuilder::with_duplicate_keys(tokenizer.options().duplicate_keys);
+    parse_document(tokenizer, &mut builder)?;
+    Ok(builder.finish().expect("a value was parsed"))
+}
+
+fn parse_document<'a, S: Source<'a>, V: Visitor<'a>>(
+    tokenizer: &mut Tokenizer<'a, S>,
+    visitor: &mut V,
+) -> Result<(), ParseError> {
+    parse_value(tokenizer, None, visitor, &mut Counters::default())?;
+    if let Some(token) = next_token(tokenizer)? {
+        return Err(ParseError::trailing_content(token));
+    }
+    Ok(())
+}
+
+/// Running totals checked against the limits of [`ParseOptions`].
+#[derive(Default)]
+struct Counters {
+    depth: usize,
+    values: usize,
+}
+
+fn parse_value<'a, S: Source<'a>, V: Visitor<'a>>(
+    tokenizer: &mut Tokenizer<'a, S>,
+    token: Option<Token<'a>>,
+    visitor: &mut V,
+    counters: &mut Counters,
+) -> Result<(), ParseError> {
     let token = if let Some(token) = token {
         token
     } else {
         unwrap_token(tokenizer)?
     };
-    let expected_tokens = vec!["null", "true", "false", "number", "string", "{", "["];
-    let value = match token.token_type {
-        TokenType::Null => Value::Null,
-        TokenType::True => Value::Bool(true),
-        TokenType::False => Value::Bool(false),
-        TokenType::Number(number) => Value::Number(number),
-        TokenType::String(string) => Value::String(string),
-        TokenType::BeginObject => parse_object(tokenizer)?,
-        TokenType::BeginArray => parse_array(tokenizer)?,
-        TokenType::Invalid => {
-            return Err(ParseError::InvalidToken(ParseErrorArgs::new(
-                token,
-                expected_tokens,
-            )))
-        }
-        _ => {
-            return Err(ParseError::UnexpectedToken(ParseErrorArgs::new(
-                token,
-                expected_tokens,
-            )))
+    let start = Position::from(token.location);
+    counters.values += 1;
+    if counters.values > tokenizer.options().max_values {
+        return Err(ParseError::ValueLimitExceeded {
+            location: token.location,
+            limit: tokenizer.options().max_values,
+        });
+    }
+    let location = token.location;
+    match token.token_type {
+        TokenType::Null => visitor.visit_null().map_err(rejected(location))?,
+        TokenType::True => visitor.visit_bool(true).map_err(rejected(location))?,
+        TokenType::False => visitor.visit_bool(false).map_err(rejected(location))?,
+        TokenType::Number(number) => visitor.visit_number(number).map_err(rejected(location))?,
+        TokenType::String(string) => visitor.visit_string(string).map_err(rejected(location))?,
+        TokenType::BeginObject | TokenType::BeginArray => {
+            let limit = tokenizer.options().max_depth;
+            if counters.depth >= limit {
+                return Err(ParseError::DepthLimitExceeded {
+                    location: token.location,
+                    limit,
+                });
+            }
+            counters.depth += 1;
+            if token.token_type == TokenType::BeginObject {
+                visitor.begin_object().map_err(rejected(location))?;
+                parse_object(tokenizer, visitor, counters)?
+            } else {
+                visitor.begin_array().map_err(rejected(location))?;
+                parse_array(tokenizer, visitor, counters)?
+            }
+            counters.depth -= 1;
         }
+        _ => return Err(unexpected_value(token)),
     };
-    Ok(value)
+    visitor
+        .visit_span(Span {
+            start,
+            end: tokenizer.end_location().into(),
+        })
+        .map_err(rejected(location))
+}
+
+/// Turns the error of a visitor callback for the token at `location` into
+/// a [`ParseError`].
+fn rejected<E: Into<Box<dyn Error + Send + Sync>>>(
+    location: Location,
+) -> impl FnOnce(E) -> ParseError {
+    move |error| ParseError::Rejected {
+        error: error.into(),
+        location,
+    }
 }
 
-fn parse_object(tokenizer: &mut Tokenizer) -> Result<Value, ParseError> {
-    let mut properties: IndexMap<String, Value> = IndexMap::new();
+/// Error for a token found where a value is expected.
+pub fn unexpected_value(token: Token) -> ParseError {
+    let expected_tokens = TokenKind::VALUE.to_vec();
+    match token.token_type {
+        TokenType::InvalidNumber(error, location) => ParseError::InvalidNumber {
+            value: token.value.into_owned(),
+            error,
+            location,
+        },
+        TokenType::InvalidString(error, location) => match error {
+            StringError::ControlCharacter(byte) => ParseError::ControlCharacterInString {
+                character: char::from(byte),
+                location,
+            },
+            StringError::LoneSurrogate(code_unit) => ParseError::LoneSurrogate {
+                code_unit,
+                location,
+            },
+            StringError::InvalidEscape => {
+                let start = location.offset - token.location.offset;
+                ParseError::InvalidEscape {
+                    sequence: token.value[start..].chars().take(location.length).collect(),
+                    location,
+                }
+            }
+        },
+        TokenType::Invalid => ParseError::InvalidToken(ParseErrorArgs::new(token, expected_tokens)),
+        _ => ParseError::UnexpectedToken(ParseErrorArgs::new(token, expected_tokens)),
+    }
+}
+
+fn parse_object<'a, S: Source<'a>, V: Visitor<'a>>(
+    tokenizer: &mut Tokenizer<'a, S>,
+    visitor: &mut V,
+    counters: &mut Counters,
+) -> Result<(), ParseError> {
+    let mut members = 0;
+    let mut keys = seen_keys(tokenizer);
     let mut had_comma = true;
-    loop {
+    let end = loop {
         let token = unwrap_token(tokenizer)?;
         if had_comma {
-            if properties.len() == 0 && token.token_type == TokenType::EndObject {
-                return Ok(Value::Object(properties));
+            if token.token_type == TokenType::EndObject && ends_container(tokenizer, members) {
+                break token.location;
             }
-            let (key, value) = parse_property(tokenizer, token)?;
-            properties.insert(key, value);
+            members += 1;
+            check_members(tokenizer, members, &token)?;
+            parse_property(tokenizer, token, visitor, counters, &mut keys)?;
         } else {
             match token.token_type {
-                TokenType::EndObject => return Ok(Value::Object(properties)),
+                TokenType::EndObject => break token.location,
                 TokenType::ValueSeparator => {}
                 _ => {
                     return Err(ParseError::UnexpectedToken(ParseErrorArgs::new(
                         token,
-                        vec!["}", ","],
+                        vec![TokenKind::EndObject, TokenKind::ValueSeparator],
                     )))
                 }
             }
         }
         had_comma = !had_comma;
-    }
+    };
+    visitor.end_object().map_err(rejected(end))
 }
 
-fn parse_property(tokenizer: &mut Tokenizer, token: Token) -> Result<(String, Value), ParseError> {
-    let key = match token.token_type {
-        TokenType::String(key) => key,
-        _ => {
-            return Err(ParseError::UnexpectedToken(ParseErrorArgs::new(
-                token,
-                vec!["string"],
-            )))
-        }
+fn parse_property<'a, S: Source<'a>, V: Visitor<'a>>(
+    tokenizer: &mut Tokenizer<'a, S>,
+    token: Token<'a>,
+    visitor: &mut V,
+    counters: &mut Counters,
+    keys: &mut SeenKeys,
+) -> Result<(), ParseError> {
+    let location = token.location;
+    let key = property_key(tokenizer, token)?;
+    check_duplicate(keys, &key, location)?;
+    let key_span = Span {
+        start: location.into(),
+        end: tokenizer.end_location().into(),
     };
     let token = unwrap_token(tokenizer)?;
     match token.token_type {
@@ -84,47 +318,807 @@ fn parse_property(tokenizer: &mut Tokenizer, token: Token) -> Result<(String, Va
         _ => {
             return Err(ParseError::UnexpectedToken(ParseErrorArgs::new(
                 token,
-                vec![":"],
+                vec![TokenKind::NameSeparator],
             )))
         }
     }
-    let value = parse_value(tokenizer, None)?;
-    Ok((key, value))
+    visitor.visit_key(key).map_err(rejected(location))?;
+    visitor.visit_span(key_span).map_err(rejected(location))?;
+    parse_value(tokenizer, None, visitor, counters)
 }
 
-fn parse_array(tokenizer: &mut Tokenizer) -> Result<Value, ParseError> {
-    let mut values: Vec<Value> = Vec::new();
+fn parse_array<'a, S: Source<'a>, V: Visitor<'a>>(
+    tokenizer: &mut Tokenizer<'a, S>,
+    visitor: &mut V,
+    counters: &mut Counters,
+) -> Result<(), ParseError> {
+    let mut members = 0;
     let mut had_comma = true;
-    loop {
+    let end = loop {
         let token = unwrap_token(tokenizer)?;
         if had_comma {
-            if values.len() == 0 && token.token_type == TokenType::EndArray {
-                return Ok(Value::Array(values));
+            if token.token_type == TokenType::EndArray && ends_container(tokenizer, members) {
+                break token.location;
             }
-            values.push(parse_value(tokenizer, Some(token))?);
+            members += 1;
+            check_members(tokenizer, members, &token)?;
+            parse_value(tokenizer, Some(token), visitor, counters)?;
         } else {
             match token.token_type {
-                TokenType::EndArray => return Ok(Value::Array(values)),
+                TokenType::EndArray => break token.location,
                 TokenType::ValueSeparator => {}
                 _ => {
                     return Err(ParseError::UnexpectedToken(ParseErrorArgs::new(
                         token,
-                        vec!["]", ","],
+                        vec![TokenKind::EndArray, TokenKind::ValueSeparator],
                     )))
                 }
             }
         }
         had_comma = !had_comma;
+    };
+    visitor.end_array().map_err(rejected(end))
+}
+
+/// Key of the property starting with `token`. JSON5 also accepts identifiers,
+/// including names like `null` that are literals elsewhere.
+pub fn property_key<'a, S: Source<'a>>(
+    tokenizer: &Tokenizer<'a, S>,
+    token: Token<'a>,
+) -> Result<Cow<'a, str>, ParseError> {
+    let is_json5 = tokenizer.options().dialect == Dialect::Json5;
+    match token.token_type {
+        TokenType::String(key) | TokenType::Identifier(key) => Ok(key),
+        TokenType::True | TokenType::False | TokenType::Null | TokenType::Number(_)
+            if is_json5 && token.value.starts_with(char::is_alphabetic) =>
+        {
+            Ok(token.value)
+        }
+        TokenType::InvalidString(..) => Err(unexpected_value(token)),
+        _ if is_json5 => Err(ParseError::UnexpectedToken(ParseErrorArgs::new(
+            token,
+            vec![TokenKind::String, TokenKind::Identifier],
+        ))),
+        _ => Err(ParseError::UnexpectedToken(ParseErrorArgs::new(
+            token,
+            vec![TokenKind::String],
+        ))),
+    }
+}
+
+/// Whether a closing bracket may follow a container's `members` when a value
+/// is expected, i.e. right after the opening bracket or a trailing comma.
+pub fn ends_container<'a, S: Source<'a>>(tokenizer: &Tokenizer<'a, S>, members: usize) -> bool {
+    members == 0 || tokenizer.options().dialect.allows_trailing_commas()
+}
+
+/// Fails once a container holds more than the allowed number of members,
+/// `token` being the first one of the latest member.
+pub fn check_members<'a, S: Source<'a>>(
+    tokenizer: &Tokenizer<'a, S>,
+    members: usize,
+    token: &Token,
+) -> Result<(), ParseError> {
+    let limit = tokenizer.options().max_members;
+    if members > limit {
+        return Err(ParseError::MemberLimitExceeded {
+            location: token.location,
+            limit,
+        });
     }
+    Ok(())
 }
 
-fn unwrap_token(tokenizer: &mut Tokenizer) -> Result<Token, ParseError> {
-    if let Some(token) = tokenizer.next_token() {
+/// Keys of an object with their locations, only tracked when duplicates are
+/// rejected.
+pub type SeenKeys = Option<HashMap<String, Location>>;
+
+pub fn seen_keys<'a, S: Source<'a>>(tokenizer: &Tokenizer<'a, S>) -> SeenKeys {
+    match tokenizer.options().duplicate_keys {
+        DuplicateKeyPolicy::Error => Some(HashMap::new()),
+        _ => None,
+    }
+}
+
+/// Records `key` found at `location`, failing if it was seen before.
+pub fn check_duplicate(
+    keys: &mut SeenKeys,
+    key: &str,
+    location: Location,
+) -> Result<(), ParseError> {
+    if let Some(keys) = keys {
+        if let Some(original) = keys.get(key) {
+            return Err(ParseError::DuplicateKey {
+                key: key.to_owned(),
+                original: *original,
+                duplicate: location,
+            });
+        }
+        keys.insert(key.to_owned(), location);
+    }
+    Ok(())
+}
+
+/// Reads the next token, giving precedence to I/O errors of the source and
+/// exceeded input limits since they cut tokens short.
+pub fn next_token<'a, S: Source<'a>>(
+    tokenizer: &mut Tokenizer<'a, S>,
+) -> Result<Option<Token<'a>>, ParseError> {
+    let token = tokenizer.next_token();
+    if let Some(error) = tokenizer.take_error() {
+        return Err(ParseError::Io(error));
+    }
+    if let Some((encoding, location)) = tokenizer.encoding_error() {
+        return Err(ParseError::InvalidEncoding { encoding, location });
+    }
+    if tokenizer.is_input_too_large() {
+        return Err(ParseError::InputSizeLimitExceeded {
+            location: tokenizer.end_location(),
+            limit: tokenizer.options().max_input_size,
+        });
+    }
+    match token {
+        Some(Token {
+            token_type: TokenType::StringTooLong(location),
+            ..
+        }) => Err(ParseError::StringLengthLimitExceeded {
+            location,
+            limit: tokenizer.options().max_string_length,
+        }),
+        Some(Token {
+            token_type: TokenType::UnterminatedComment,
+            location,
+            ..
+        }) => Err(ParseError::UnterminatedComment(location)),
+        token => Ok(token),
+    }
+}
+
+pub fn unwrap_token<'a, S: Source<'a>>(
+    tokenizer: &mut Tokenizer<'a, S>,
+) -> Result<Token<'a>, ParseError> {
+    if let Some(token) = next_token(tokenizer)? {
         return Ok(token);
     };
-    return Err(ParseError::UnexpectedEndOfFile(Location {
-        line: tokenizer.line,
-        column: tokenizer.column,
-        length: 1,
-    }));
+    Err(ParseError::UnexpectedEndOfFile(tokenizer.end_location()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{
+        number::{Number, NumberError},
+        options::{Dialect, DuplicateKeyPolicy, DEFAULT_MAX_DEPTH},
+        parse_error::ErrorKind,
+        parse_recovering,
+        test_readers::{ByteReader, FailingReader},
+    };
+    use std::{borrow::Cow, convert::Infallible};
+    use test_case::test_case;
+
+    #[test_case(r#"{"a":1} garbage"#, "garbage", 1, 9 ; "Literal after object")]
+    #[test_case("1 2 3", "2", 1, 3 ; "Several numbers")]
+    #[test_case("[]\n]", "]", 2, 1 ; "Closing bracket on next line")]
+    fn trailing_content(json: &str, expected: &str, line: usize, column: usize) {
+        let Err(ParseError::TrailingContent { found, location }) = parse(json) else {
+            panic!("Expected trailing content error");
+        };
+        assert_eq!(found, expected);
+        assert_eq!((line, column), (location.line, location.column));
+    }
+
+    #[test]
+    fn trailing_whitespace() {
+        assert_eq!(parse(" true \n\t").unwrap(), Value::Bool(true));
+    }
+
+    #[test_case(r#"{"a":1} garbage"#, 7 ; "Object followed by text")]
+    #[test_case("1 2 3", 1 ; "Several numbers")]
+    #[test_case(r#""ü" tail"#, 4 ; "Offset counts bytes")]
+    fn prefix(json: &str, expected_offset: usize) {
+        let (_, offset) = parse_prefix(json).unwrap();
+        assert_eq!(offset, expected_offset);
+    }
+
+    #[test_case("[+1]", 2, NumberError::LeadingPlus ; "Leading plus")]
+    #[test_case("[1, 01]", 5, NumberError::LeadingZero ; "Leading zero")]
+    #[test_case("-007", 2, NumberError::LeadingZero ; "Negative leading zero")]
+    #[test_case("1.", 3, NumberError::MissingFractionDigits ; "Trailing decimal point")]
+    #[test_case("1.e5", 3, NumberError::MissingFractionDigits ; "Exponent after decimal point")]
+    #[test_case(".5", 1, NumberError::MissingIntegerDigits ; "Leading decimal point")]
+    #[test_case("--3", 2, NumberError::MissingIntegerDigits ; "Double minus")]
+    #[test_case("-", 2, NumberError::MissingIntegerDigits ; "Lone minus")]
+    #[test_case("1e", 3, NumberError::MissingExponentDigits ; "Missing exponent")]
+    #[test_case("1e+", 4, NumberError::MissingExponentDigits ; "Missing exponent after sign")]
+    fn invalid_numbers(json: &str, expected_column: usize, expected_error: NumberError) {
+        let Err(ParseError::InvalidNumber {
+            value,
+            error,
+            location,
+        }) = parse(json)
+        else {
+            panic!("Expected invalid number error");
+        };
+        assert_eq!(error, expected_error);
+        assert_eq!(location.column, expected_column);
+        assert!(json.contains(&value));
+    }
+
+    #[test_case("0.22e2" ; "Exponent notation")]
+    #[test_case("12345678901234567890.123456789" ; "More digits than f64 holds")]
+    #[test_case("-0.0" ; "Negative zero")]
+    #[test_case("1E+400" ; "Beyond f64 range")]
+    fn lossless_round_trip(lexeme: &str) {
+        let json = format!("[{}]", lexeme);
+        let value = parse_with(&json, ParseOptions::new().lossless_numbers(true)).unwrap();
+        assert_eq!(
+            value.stringified().indent(0).create(),
+            format!("[\n{}\n]", lexeme)
+        );
+    }
+
+    #[test]
+    fn borrowed_strings() {
+        let json = r#"{"plain": "text", "escaped\n": "a\u0041"}"#;
+        let BorrowedValue::Object(object) = parse_borrowed(json).unwrap() else {
+            panic!("Expected object");
+        };
+        let (plain_key, plain_value) = object.get_index(0).unwrap();
+        assert!(matches!(plain_key, Cow::Borrowed("plain")));
+        assert!(matches!(
+            plain_value,
+            BorrowedValue::String(Cow::Borrowed("text"))
+        ));
+        let (escaped_key, escaped_value) = object.get_index(1).unwrap();
+        assert!(matches!(escaped_key, Cow::Owned(key) if key == "escaped\n"));
+        assert!(matches!(escaped_value, BorrowedValue::String(Cow::Owned(value)) if value == "aA"));
+    }
+
+    #[test]
+    fn borrowed_into_owned() {
+        let json = r#"[{"a": [null, true, 1.5, "\""]}]"#;
+        assert_eq!(
+            parse_borrowed(json).unwrap().into_owned(),
+            parse(json).unwrap()
+        );
+    }
+
+    #[test]
+    fn reader() {
+        let json = r#"{"a": [1, "b"]}"#;
+        assert_eq!(parse_reader(json.as_bytes()).unwrap(), parse(json).unwrap());
+    }
+
+    #[test]
+    fn reader_error() {
+        let reader = "[1, 2".as_bytes().chain(FailingReader);
+        assert!(matches!(parse_reader(reader), Err(ParseError::Io(_))));
+    }
+
+    /// Collects the callbacks as strings to check their order.
+    #[derive(Default)]
+    struct Recorder(Vec<String>);
+
+    impl<'a> Visitor<'a> for Recorder {
+        type Error = Infallible;
+
+        fn visit_null(&mut self) -> Result<(), Self::Error> {
+            self.0.push("null".to_string());
+            Ok(())
+        }
+        fn visit_bool(&mut self, bool: bool) -> Result<(), Self::Error> {
+            self.0.push(bool.to_string());
+            Ok(())
+        }
+        fn visit_number(&mut self, number: Number) -> Result<(), Self::Error> {
+            self.0.push(number.to_string());
+            Ok(())
+        }
+        fn visit_string(&mut self, string: Cow<'a, str>) -> Result<(), Self::Error> {
+            self.0.push(format!("{:?}", string));
+            Ok(())
+        }
+        fn begin_object(&mut self) -> Result<(), Self::Error> {
+            self.0.push("{".to_string());
+            Ok(())
+        }
+        fn visit_key(&mut self, key: Cow<'a, str>) -> Result<(), Self::Error> {
+            self.0.push(format!("{}:", key));
+            Ok(())
+        }
+        fn end_object(&mut self) -> Result<(), Self::Error> {
+            self.0.push("}".to_string());
+            Ok(())
+        }
+        fn begin_array(&mut self) -> Result<(), Self::Error> {
+            self.0.push("[".to_string());
+            Ok(())
+        }
+        fn end_array(&mut self) -> Result<(), Self::Error> {
+            self.0.push("]".to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn visitor_callbacks() {
+        let mut recorder = Recorder::default();
+        let json = r#"{"a": [1, null], "b": {"c": "d"}, "e": false}"#;
+        visit(json, ParseOptions::default(), &mut recorder).unwrap();
+        assert_eq!(
+            recorder.0.join(" "),
+            r#"{ a: [ 1 null ] b: { c: "d" } e: false }"#
+        );
+    }
+
+    /// Collects port numbers, rejecting anything else.
+    #[derive(Default)]
+    struct Ports(Vec<u16>);
+
+    impl<'a> Visitor<'a> for Ports {
+        type Error = String;
+
+        fn visit_null(&mut self) -> Result<(), Self::Error> {
+            Err("Expected a port, found null".to_string())
+        }
+        fn visit_bool(&mut self, _: bool) -> Result<(), Self::Error> {
+            Err("Expected a port, found a boolean".to_string())
+        }
+        fn visit_number(&mut self, number: Number) -> Result<(), Self::Error> {
+            let port = number.as_i128().and_then(|port| u16::try_from(port).ok());
+            self.0
+                .push(port.ok_or(format!("Port {} out of range", number))?);
+            Ok(())
+        }
+        fn visit_string(&mut self, _: Cow<'a, str>) -> Result<(), Self::Error> {
+            Err("Expected a port, found a string".to_string())
+        }
+        fn begin_object(&mut self) -> Result<(), Self::Error> {
+            Err("Expected a port, found an object".to_string())
+        }
+        fn visit_key(&mut self, _: Cow<'a, str>) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn end_object(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn begin_array(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn end_array(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    #[test_case("[80, 443]", vec![80, 443], None ; "Accepted")]
+    #[test_case("[80, 70000, 443]", vec![80], Some(((1, 6), "Port 70000 out of range")) ; "Rejected number")]
+    #[test_case("[80,\n {}, 443]", vec![80], Some(((2, 2), "Expected a port, found an object")) ; "Rejected container")]
+    fn visitor_rejects(json: &str, visited: Vec<u16>, expected: Option<((usize, usize), &str)>) {
+        let mut ports = Ports::default();
+        let error = visit(json, ParseOptions::default(), &mut ports).err();
+        let error = error.map(|error| {
+            assert_eq!(error.kind(), ErrorKind::Rejected);
+            let location = error.location().unwrap();
+            (
+                (location.line, location.column),
+                error.source().unwrap().to_string(),
+            )
+        });
+        assert_eq!(
+            error,
+            expected.map(|(at, message)| (at, message.to_string()))
+        );
+        // Parsing stops at the rejected value.
+        assert_eq!(ports.0, visited);
+    }
+
+    #[test]
+    fn deep_nesting() {
+        let json = "[".repeat(100_000);
+        let Err(ParseError::DepthLimitExceeded { location, limit }) = parse(&json) else {
+            panic!("Expected depth limit error");
+        };
+        assert_eq!(limit, DEFAULT_MAX_DEPTH);
+        assert_eq!(location.column, DEFAULT_MAX_DEPTH + 1);
+    }
+
+    #[test_case(2, r#"[{"a": 1}]"#, true ; "At the limit")]
+    #[test_case(2, r#"[{"a": []}]"#, false ; "Beyond the limit")]
+    #[test_case(0, "1", true ; "Scalar without nesting")]
+    #[test_case(0, "[]", false ; "Zero depth")]
+    fn depth_limit(max_depth: usize, json: &str, is_ok: bool) {
+        let result = parse_with(json, ParseOptions::new().max_depth(max_depth));
+        assert_eq!(result.is_ok(), is_ok);
+    }
+
+    #[test_case(ParseOptions::new().max_input_size(8), "[1, 2, 3]", (1, 9) ; "Input size")]
+    #[test_case(ParseOptions::new().max_string_length(3), r#"["abc", "abcd"]"#, (1, 13) ; "String length")]
+    #[test_case(ParseOptions::new().max_string_length(3), r#"{"abcd": 1}"#, (1, 6) ; "Key length")]
+    #[test_case(ParseOptions::new().max_string_length(3), r#"["ab\u00fc"]"#, (1, 5) ; "Decoded escape")]
+    #[test_case(ParseOptions::new().max_string_length(4), r#"["abcü"]"#, (1, 6) ; "Multi-byte character")]
+    #[test_case(ParseOptions::new().max_members(2), "[[1, 2], [3, 4, 5]]", (1, 17) ; "Array elements")]
+    #[test_case(ParseOptions::new().max_members(1), r#"{"a": 1, "b": 2}"#, (1, 10) ; "Object properties")]
+    #[test_case(ParseOptions::new().max_values(4), "[1, [2, 3]]", (1, 9) ; "Total values")]
+    fn limits(options: ParseOptions, json: &str, expected: (usize, usize)) {
+        let error = parse_with(json, options.clone()).unwrap_err();
+        let location = match &error {
+            ParseError::InputSizeLimitExceeded { location, .. }
+            | ParseError::StringLengthLimitExceeded { location, .. }
+            | ParseError::MemberLimitExceeded { location, .. }
+            | ParseError::ValueLimitExceeded { location, .. } => location,
+            error => panic!("Expected limit error, got {:?}", error),
+        };
+        assert_eq!((location.line, location.column), expected);
+        let reader_error = parse_reader_with(json.as_bytes(), options).unwrap_err();
+        assert_eq!(reader_error.to_string(), error.to_string());
+    }
+
+    #[test]
+    fn prefix_value() {
+        let (value, offset) = parse_prefix("[1] [2]").unwrap();
+        assert_eq!(
+            value,
+            Value::Array(vec![Value::Number(Number::UnsingedInteger(1))])
+        );
+        assert_eq!(offset, 3);
+    }
+
+    #[test_case(DuplicateKeyPolicy::FirstWins, r#"{"a": 1, "b": 2}"# ; "First wins")]
+    #[test_case(DuplicateKeyPolicy::LastWins, r#"{"a": 4, "b": 2}"# ; "Last wins")]
+    #[test_case(DuplicateKeyPolicy::Collect, r#"{"a": [1, [3], 4], "b": 2}"# ; "Collect")]
+    fn duplicate_keys(policy: DuplicateKeyPolicy, expected: &str) {
+        let json = r#"{"a": 1, "b": 2, "a": [3], "a": 4}"#;
+        let options = ParseOptions::new().duplicate_keys(policy);
+        assert_eq!(
+            parse_with(json, options.clone()).unwrap(),
+            parse(expected).unwrap()
+        );
+        let borrowed = parse_borrowed_with(json, options).unwrap();
+        assert_eq!(borrowed.into_owned(), parse(expected).unwrap());
+    }
+
+    #[test]
+    fn duplicate_key_error() {
+        let json = "{\n  \"a\": 1,\n  \"b\": {\"a\": 2},\n  \"a\": 3\n}";
+        let options = ParseOptions::new().duplicate_keys(DuplicateKeyPolicy::Error);
+        let error = parse_with(json, options).unwrap_err();
+        let ParseError::DuplicateKey {
+            key,
+            original,
+            duplicate,
+        } = &error
+        else {
+            panic!("Expected duplicate key error");
+        };
+        assert_eq!(key, "a");
+        assert_eq!((original.line, original.column), (2, 3));
+        assert_eq!((duplicate.line, duplicate.column), (4, 3));
+        assert_eq!(
+            error.to_string(),
+            ">> Parsing Error on line 4 column 3: Duplicate key `a`, first defined on line 2 column 3"
+        );
+    }
+
+    #[test]
+    fn jsonc() {
+        let json = r#"{
+            // Editor settings
+            "editor.tabSize": 4, /* spaces */
+            "files.exclude": [
+                "target", // build output
+            ],
+        }"#;
+        let expected = parse(r#"{"editor.tabSize": 4, "files.exclude": ["target"]}"#);
+        let options = ParseOptions::new().dialect(Dialect::Jsonc);
+        assert_eq!(parse_with(json, options).unwrap(), expected.unwrap());
+        assert!(parse(json).is_err());
+    }
+
+    #[test_case("[1,,]" ; "Double comma")]
+    #[test_case("[,]" ; "Lone comma")]
+    #[test_case("{,}" ; "Lone comma in object")]
+    #[test_case("[1] /" ; "Lone slash")]
+    fn invalid_jsonc(json: &str) {
+        assert!(parse_with(json, ParseOptions::new().dialect(Dialect::Jsonc)).is_err());
+    }
+
+    #[test]
+    fn unterminated_comment() {
+        let options = ParseOptions::new().dialect(Dialect::Jsonc);
+        let error = parse_with("{\n  \"a\": 1 /* note\n}", options).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            ">> Parsing Error on line 2 column 10: Unterminated block comment, expected `*/`"
+        );
+    }
+
+    #[test]
+    fn json5() {
+        let json = r#"// https://spec.json5.org/#introduction
+{
+  // comments
+  unquoted: 'and you can quote me on that',
+  singleQuotes: 'I can use "double quotes" here',
+  lineBreaks: "Look, Mom! \
+No \\n's!",
+  hexadecimal: 0xdecaf,
+  leadingDecimalPoint: .8675309, andTrailing: 8675309.,
+  positiveSign: +1,
+  trailingComma: 'in objects', andIn: ['arrays',],
+  "backwardsCompatible": "with JSON",
+}"#;
+        let expected = parse(
+            r#"{
+  "unquoted": "and you can quote me on that",
+  "singleQuotes": "I can use \"double quotes\" here",
+  "lineBreaks": "Look, Mom! No \\n's!",
+  "hexadecimal": 912559,
+  "leadingDecimalPoint": 0.8675309, "andTrailing": 8675309,
+  "positiveSign": 1,
+  "trailingComma": "in objects", "andIn": ["arrays"],
+  "backwardsCompatible": "with JSON"
+}"#,
+        );
+        assert_eq!(parse_with(json, Dialect::Json5).unwrap(), expected.unwrap());
+        assert!(parse_with(json, Dialect::Jsonc).is_err());
+    }
+
+    #[test_case("0x1F", Number::UnsingedInteger(31) ; "Hexadecimal")]
+    #[test_case("-0XfF", Number::Integer(-255) ; "Negative hexadecimal")]
+    #[test_case("-0x8000000000000000", Number::Integer(i64::MIN) ; "Smallest hexadecimal integer")]
+    #[test_case("0x10000000000000000", Number::Float(18446744073709551616.0) ; "Hexadecimal beyond u64")]
+    #[test_case("+1.5", Number::Float(1.5) ; "Explicit plus")]
+    #[test_case("-.5e1", Number::Integer(-5) ; "Leading decimal point")]
+    #[test_case("5.", Number::UnsingedInteger(5) ; "Trailing decimal point")]
+    #[test_case("Infinity", Number::Float(f64::INFINITY) ; "Infinity")]
+    #[test_case("-Infinity", Number::Float(f64::NEG_INFINITY) ; "Negative infinity")]
+    fn json5_numbers(json: &str, expected: Number) {
+        assert_eq!(
+            parse_with(json, Dialect::Json5).unwrap(),
+            Value::Number(expected)
+        );
+    }
+
+    #[test]
+    fn json5_lossless_numbers() {
+        let options = ParseOptions::new()
+            .dialect(Dialect::Json5)
+            .lossless_numbers(true);
+        let value = parse_with("[+.5, 1.e3, 0x10, NaN]", options).unwrap();
+        let Value::Array(numbers) = value else {
+            panic!("Expected array");
+        };
+        assert_eq!(numbers[0], Value::Number(Number::Raw("0.5".to_owned())));
+        assert_eq!(numbers[1], Value::Number(Number::Raw("1e3".to_owned())));
+        assert_eq!(numbers[2], Value::Number(Number::UnsingedInteger(16)));
+        assert!(matches!(numbers[3], Value::Number(Number::Float(nan)) if nan.is_nan()));
+    }
+
+    #[test_case(r#"'\'\"'"#, "'\"" ; "Escaped quotes")]
+    #[test_case(r#"'\x41\v\0'"#, "A\u{b}\0" ; "Additional escapes")]
+    #[test_case(r#"'\q\é'"#, "qé" ; "Characters escaping themselves")]
+    #[test_case("'a\\\r\nb\\\u{2028}c'", "abc" ; "Line continuations")]
+    fn json5_strings(json: &str, expected: &str) {
+        assert_eq!(
+            parse_with(json, Dialect::Json5).unwrap(),
+            Value::String(expected.to_owned())
+        );
+    }
+
+    #[test_case(r#"{$_id: 1, ünïcode: 2, ab: 3, null: 4, NaN: 5}"#, &["$_id", "ünïcode", "ab", "null", "NaN"] ; "Identifiers")]
+    fn json5_keys(json: &str, expected: &[&str]) {
+        let Value::Object(object) = parse_with(json, Dialect::Json5).unwrap() else {
+            panic!("Expected object");
+        };
+        assert_eq!(object.keys().collect::<Vec<_>>(), expected);
+    }
+
+    #[test_case("0x" ; "Missing hex digits")]
+    #[test_case("01" ; "Leading zero")]
+    #[test_case("." ; "Lone decimal point")]
+    #[test_case(r#"'\1'"# ; "Digit escape")]
+    #[test_case(r#"'\01'"# ; "Octal escape")]
+    #[test_case("{-Infinity: 1}" ; "Signed key")]
+    #[test_case("{1a: 1}" ; "Key starting with digit")]
+    #[test_case("[Infinit]" ; "Misspelled literal")]
+    #[test_case("[undefined]" ; "Identifier value")]
+    #[test_case("'open" ; "Unterminated string")]
+    fn invalid_json5(json: &str) {
+        assert!(parse_with(json, Dialect::Json5).is_err());
+    }
+
+    #[test]
+    fn json5_output() {
+        let json = r#"{"name": "say \"hi\"", "not an identifier": [1, -Infinity, NaN]}"#;
+        let value = parse_with(json, Dialect::Json5).unwrap();
+        let json5 = value.stringified().json5(true).create();
+        assert_eq!(
+            json5,
+            "{\n  name: 'say \"hi\"',\n  \"not an identifier\": [\n    1,\n    -Infinity,\n    NaN,\n  ],\n}"
+        );
+        let round_trip = parse_with(&json5, Dialect::Json5).unwrap();
+        assert_eq!(
+            round_trip.stringified().create(),
+            value.stringified().create()
+        );
+        assert_eq!(
+            parse(&value.stringified().indent(0).create()).unwrap(),
+            parse(r#"{"name": "say \"hi\"", "not an identifier": [1, null, null]}"#).unwrap()
+        );
+    }
+
+    #[test_case("\"a\u{1}b\"", ErrorKind::ControlCharacterInString, (1, 3, 1) ; "Raw control character")]
+    #[test_case("[\"line\nbreak\"]", ErrorKind::ControlCharacterInString, (1, 7, 1) ; "Raw line break")]
+    #[test_case("\"\t\"", ErrorKind::ControlCharacterInString, (1, 2, 1) ; "Raw tab")]
+    #[test_case(r#""\uD800""#, ErrorKind::LoneSurrogate, (1, 2, 6) ; "High surrogate at end")]
+    #[test_case(r#""\uD800A""#, ErrorKind::LoneSurrogate, (1, 2, 6) ; "High surrogate before other escape")]
+    #[test_case(r#""\uD800\uD800""#, ErrorKind::LoneSurrogate, (1, 2, 6) ; "Two high surrogates")]
+    #[test_case(r#""ab\uDC00""#, ErrorKind::LoneSurrogate, (1, 4, 6) ; "Lone low surrogate")]
+    #[test_case(r#""\q""#, ErrorKind::InvalidEscape, (1, 2, 2) ; "Unknown escape")]
+    #[test_case(r#""\u12G4""#, ErrorKind::InvalidEscape, (1, 2, 5) ; "Invalid hex digit")]
+    #[test_case(r#"{"\x": 1}"#, ErrorKind::InvalidEscape, (1, 3, 2) ; "Invalid escape in key")]
+    fn invalid_strings(json: &str, kind: ErrorKind, (line, column, length): (usize, usize, usize)) {
+        let error = parse(json).unwrap_err();
+        assert_eq!(error.kind(), kind);
+        let location = error.location().unwrap();
+        assert_eq!(
+            (location.line, location.column, location.length),
+            (line, column, length)
+        );
+    }
+
+    #[test]
+    fn invalid_string_messages() {
+        let messages = [r#"["\u12G4"]"#, "[\"\u{1f}\"]", r#"["\uDE00"]"#]
+            .map(|json| parse(json).unwrap_err().to_string());
+        assert_eq!(
+            messages,
+            [
+                ">> Parsing Error on line 1 column 3: Invalid escape sequence `\\u12G` in string",
+                ">> Parsing Error on line 1 column 3: Unescaped control character U+001F in string",
+                ">> Parsing Error on line 1 column 3: Unpaired surrogate `\\uDE00` in string",
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_string_recovery() {
+        let (value, errors) = parse_recovering("[\"\\q\\\"\", \"a\u{0}\", 1]");
+        assert_eq!(value, parse("[null, null, 1]").unwrap());
+        let kinds: Vec<_> = errors.iter().map(ParseError::kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                ErrorKind::InvalidEscape,
+                ErrorKind::ControlCharacterInString
+            ]
+        );
+    }
+
+    #[test_case(r#""\uD800""#, "\u{FFFD}" ; "High surrogate at end")]
+    #[test_case(r#""\uD800A""#, "\u{FFFD}A" ; "High surrogate before other escape")]
+    #[test_case(r#""\uDC00𝄞""#, "\u{FFFD}𝄞" ; "Lone low surrogate before pair")]
+    fn replace_lone_surrogates(json: &str, expected: &str) {
+        let options = ParseOptions::new().replace_lone_surrogates(true);
+        assert_eq!(
+            parse_with(json, options).unwrap(),
+            Value::String(expected.to_owned())
+        );
+    }
+
+    #[test]
+    fn json5_control_characters() {
+        assert_eq!(
+            parse_with("'a\tb'", Dialect::Json5).unwrap(),
+            Value::String("a\tb".to_owned())
+        );
+        let error = parse_with("'a\nb'", Dialect::Json5).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::ControlCharacterInString);
+    }
+
+    #[test_case("" ; "Empty input")]
+    #[test_case(" \n\t" ; "Only whitespace")]
+    #[test_case("\u{FEFF}" ; "Only byte order mark")]
+    fn empty_input(json: &str) {
+        let error = parse(json).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::UnexpectedEndOfFile);
+        assert_eq!(
+            parse_reader(json.as_bytes()).unwrap_err().kind(),
+            ErrorKind::UnexpectedEndOfFile
+        );
+    }
+
+    #[test]
+    fn byte_order_mark() {
+        let json = "\u{FEFF}[1, x]";
+        let error = parse(json).unwrap_err();
+        let location = error.location().unwrap();
+        assert_eq!((location.column, location.offset), (5, 7));
+        assert_eq!(
+            parse_reader("\u{FEFF}[1]".as_bytes()).unwrap(),
+            parse("[1]").unwrap()
+        );
+        assert_eq!(
+            parse_bytes("\u{FEFF}[1]".as_bytes()).unwrap(),
+            parse("[1]").unwrap()
+        );
+    }
+
+    fn encode_utf16(json: &str, little_endian: bool) -> Vec<u8> {
+        json.encode_utf16()
+            .flat_map(|unit| {
+                if little_endian {
+                    unit.to_le_bytes()
+                } else {
+                    unit.to_be_bytes()
+                }
+            })
+            .collect()
+    }
+
+    fn encode_utf32(json: &str, little_endian: bool) -> Vec<u8> {
+        json.chars()
+            .flat_map(|character| {
+                if little_endian {
+                    (character as u32).to_le_bytes()
+                } else {
+                    (character as u32).to_be_bytes()
+                }
+            })
+            .collect()
+    }
+
+    #[test_case(encode_utf16, true ; "UTF-16LE")]
+    #[test_case(encode_utf16, false ; "UTF-16BE")]
+    #[test_case(encode_utf32, true ; "UTF-32LE")]
+    #[test_case(encode_utf32, false ; "UTF-32BE")]
+    fn bytes(encode: fn(&str, bool) -> Vec<u8>, little_endian: bool) {
+        let json = r#"{"grüße": ["𝄞", 1]}"#;
+        let expected = parse(json).unwrap();
+        assert_eq!(parse_bytes(&encode(json, little_endian)).unwrap(), expected);
+        let with_bom = format!("\u{FEFF}{}", json);
+        assert_eq!(
+            parse_bytes(&encode(&with_bom, little_endian)).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let json = b"[\"ok\",\n \"a\xFFb\"]";
+        let error = parse_bytes(json).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidEncoding);
+        assert_eq!(
+            error.to_string(),
+            ">> Parsing Error on line 2 column 4: Invalid UTF-8 sequence"
+        );
+        assert_eq!(error.location().unwrap().offset, 10);
+        let error = parse_reader(&json[..]).unwrap_err();
+        assert_eq!(error.location().unwrap().offset, 10);
+    }
+
+    #[test]
+    fn syntax_error_before_invalid_utf8() {
+        let json = b"[1 2, \"\xC3\"]";
+        assert_eq!(
+            parse_bytes(json).unwrap_err().kind(),
+            ErrorKind::UnexpectedToken
+        );
+        assert_eq!(
+            parse_reader(&json[..]).unwrap_err().kind(),
+            ErrorKind::UnexpectedToken
+        );
+    }
+
+    #[test]
+    fn utf8_split_across_reads() {
+        let json = r#"["grüße", "𝄞"]"#;
+        assert_eq!(
+            parse_reader(ByteReader(json.as_bytes())).unwrap(),
+            parse(json).unwrap()
+        );
+        let error = parse_reader(ByteReader(b"[\"\xF0\x9D\x84")).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidEncoding);
+        assert_eq!(error.location().unwrap().offset, 2);
+    }
 }
@@ -0,0 +1,399 @@
+use super::{
+    options::ParseOptions,
+    parse_error::{ParseError, ParseErrorArgs, TokenKind},
+    parser::{
+        check_duplicate, ends_container, next_token, property_key, seen_keys, unexpected_value,
+    },
+    tokenizer::{Token, TokenType, Tokenizer},
+    value::Value,
+    visitor::{TreeBuilder, Visitor},
+};
+
+/// Parses `json` without stopping at the first error. After an error the
+/// parser skips ahead to the next `,`, `}` or `]` and carries on, so every
+/// problem in the document is reported. Broken values are replaced by
+/// `null` and broken object members are left out.
+///
+/// Exceeding the input size or string length limit and an unterminated
+/// comment end the input, the member and value limits are not enforced.
+pub fn parse_recovering(json: &str) -> (Value, Vec<ParseError>) {
+    parse_recovering_with(json, ParseOptions::default())
+}
+
+/// Like [`parse_recovering`], but configured by `options`.
+pub fn parse_recovering_with(
+    json: &str,
+    options: impl Into<ParseOptions>,
+) -> (Value, Vec<ParseError>) {
+    let tokenizer = Tokenizer::with_options(json, options.into());
+    let mut builder = TreeBuilder::with_duplicate_keys(tokenizer.options().duplicate_keys);
+    let mut parser = RecoveringParser {
+        tokenizer,
+        peeked: None,
+        errors: Vec::new(),
+        last_error_offset: None,
+        is_truncated: false,
+        depth: 0,
+    };
+    let Ok(()) = parser.value(&mut builder);
+    if let Some(token) = parser.next() {
+        let offset = token.location.offset;
+        parser.report(ParseError::trailing_content(token), offset);
+    }
+    let value = builder.finish().unwrap_or(Value::Null);
+    (value, parser.errors)
+}
+
+struct RecoveringParser<'a> {
+    tokenizer: Tokenizer<'a>,
+    /// A token read ahead or put back.
+    peeked: Option<Token<'a>>,
+    errors: Vec<ParseError>,
+    /// Offset of the token the last error was reported for, so a single
+    /// mistake is reported once even if several rules stumble over it.
+    last_error_offset: Option<usize>,
+    /// Set once the end of input was reached unexpectedly and reported.
+    is_truncated: bool,
+    /// Number of open containers.
+    depth: usize,
+}
+
+impl<'a> RecoveringParser<'a> {
+    fn peek(&mut self) -> Option<&Token<'a>> {
+        if self.peeked.is_none() && !self.is_truncated {
+            match next_token(&mut self.tokenizer) {
+                Ok(token) => self.peeked = token,
+                Err(error) => {
+                    let offset = self.tokenizer.offset();
+                    self.report(error, offset);
+                    self.is_truncated = true;
+                }
+            }
+        }
+        self.peeked.as_ref()
+    }
+
+    fn peek_type(&mut self) -> Option<&TokenType<'a>> {
+        self.peek().map(|token| &token.token_type)
+    }
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        self.peek();
+        self.peeked.take()
+    }
+
+    /// Like [`next`](Self::next), but reports a missing token.
+    fn expect(&mut self) -> Option<Token<'a>> {
+        let token = self.next();
+        if token.is_none() {
+            self.end_of_input();
+        }
+        token
+    }
+
+    fn end_of_input(&mut self) {
+        if !self.is_truncated {
+            let location = self.tokenizer.end_location();
+            self.report(ParseError::UnexpectedEndOfFile(location), location.offset);
+            self.is_truncated = true;
+        }
+    }
+
+    fn report(&mut self, error: ParseError, offset: usize) {
+        if self.last_error_offset != Some(offset) {
+            self.errors.push(error);
+            self.last_error_offset = Some(offset);
+        }
+    }
+
+    /// Reports `token` as unexpected and puts it back for recovery.
+    fn unexpected(&mut self, token: Token<'a>, expected_tokens: Vec<TokenKind>) {
+        let error =
+            ParseError::UnexpectedToken(ParseErrorArgs::new(token.clone(), expected_tokens));
+        self.report(error, token.location.offset);
+        self.peeked = Some(token);
+    }
+
+    fn value<V: Visitor<'a>>(&mut self, visitor: &mut V) -> Result<(), V::Error> {
+        let Some(token) = self.expect() else {
+            return visitor.visit_null();
+        };
+        let offset = token.location.offset;
+        if matches!(
+            token.token_type,
+            TokenType::ValueSeparator | TokenType::EndObject | TokenType::EndArray
+        ) {
+            // Left for the enclosing container to synchronise on.
+            self.report(unexpected_value(token.clone()), offset);
+            self.peeked = Some(token);
+            return visitor.visit_null();
+        }
+        match token.token_type {
+            TokenType::Null => visitor.visit_null(),
+            TokenType::True => visitor.visit_bool(true),
+            TokenType::False => visitor.visit_bool(false),
+            TokenType::Number(number) => visitor.visit_number(number),
+            TokenType::String(string) => visitor.visit_string(string),
+            TokenType::BeginObject | TokenType::BeginArray
+                if self.depth >= self.tokenizer.options().max_depth =>
+            {
+                let limit = self.tokenizer.options().max_depth;
+                let location = token.location;
+                self.report(ParseError::DepthLimitExceeded { location, limit }, offset);
+                self.skip_nested(1);
+                visitor.visit_null()
+            }
+            TokenType::BeginObject => {
+                self.depth += 1;
+                self.object(visitor)?;
+                self.depth -= 1;
+                Ok(())
+            }
+            TokenType::BeginArray => {
+                self.depth += 1;
+                self.array(visitor)?;
+                self.depth -= 1;
+                Ok(())
+            }
+            _ => {
+                self.report(unexpected_value(token), offset);
+                self.synchronize();
+                visitor.visit_null()
+            }
+        }
+    }
+
+    fn object<V: Visitor<'a>>(&mut self, visitor: &mut V) -> Result<(), V::Error> {
+        visitor.begin_object()?;
+        let mut members = 0;
+        let mut keys = seen_keys(&self.tokenizer);
+        while let Some(token) = self.expect() {
+            if token.token_type == TokenType::EndObject {
+                if !ends_container(&self.tokenizer, members) {
+                    self.unexpected(token, vec![TokenKind::String]);
+                    self.next();
+                }
+                break;
+            }
+            if matches!(
+                token.token_type,
+                TokenType::ValueSeparator | TokenType::EndArray
+            ) {
+                self.unexpected(token, vec![TokenKind::String, TokenKind::EndObject]);
+                if self.recover(TokenType::EndObject) {
+                    continue;
+                }
+                break;
+            }
+            members += 1;
+            let location = token.location;
+            let key = match property_key(&self.tokenizer, token) {
+                Ok(key) => key,
+                Err(error) => {
+                    self.report(error, location.offset);
+                    if self.recover(TokenType::EndObject) {
+                        continue;
+                    }
+                    break;
+                }
+            };
+            let colon = self.expect();
+            let error = match colon {
+                Some(token) if token.token_type == TokenType::NameSeparator => {
+                    check_duplicate(&mut keys, &key, location).err()
+                }
+                Some(token) => {
+                    self.unexpected(token, vec![TokenKind::NameSeparator]);
+                    None
+                }
+                None => break,
+            };
+            if let Some(error) = error {
+                self.report(error, location.offset);
+            } else if self.peeked.is_none() {
+                // No token was put back, so the colon was there.
+                visitor.visit_key(key)?;
+                self.value(visitor)?;
+                if self.separator(
+                    TokenType::EndObject,
+                    vec![TokenKind::EndObject, TokenKind::ValueSeparator],
+                ) {
+                    continue;
+                }
+                break;
+            }
+            // Drop the broken member.
+            if !self.recover(TokenType::EndObject) {
+                break;
+            }
+        }
+        visitor.end_object()
+    }
+
+    fn array<V: Visitor<'a>>(&mut self, visitor: &mut V) -> Result<(), V::Error> {
+        visitor.begin_array()?;
+        let mut members = 0;
+        loop {
+            match self.peek_type() {
+                None => {
+                    self.end_of_input();
+                    break;
+                }
+                Some(TokenType::EndArray) => {
+                    let token = self.next().expect("the token was peeked");
+                    if !ends_container(&self.tokenizer, members) {
+                        let offset = token.location.offset;
+                        self.report(unexpected_value(token), offset);
+                    }
+                    break;
+                }
+                Some(_) => {}
+            }
+            members += 1;
+            self.value(visitor)?;
+            if !self.separator(
+                TokenType::EndArray,
+                vec![TokenKind::EndArray, TokenKind::ValueSeparator],
+            ) {
+                break;
+            }
+        }
+        visitor.end_array()
+    }
+
+    /// Consumes the `,` or `closing` bracket after a member, recovering from
+    /// anything else. Returns whether the container continues.
+    fn separator(&mut self, closing: TokenType<'a>, expected_tokens: Vec<TokenKind>) -> bool {
+        let Some(token) = self.expect() else {
+            return false;
+        };
+        match token.token_type {
+            TokenType::ValueSeparator => true,
+            _ if token.token_type == closing => false,
+            _ => {
+                self.unexpected(token, expected_tokens);
+                self.recover(closing)
+            }
+        }
+    }
+
+    /// Skips to the next `,` or closing bracket after an error and consumes
+    /// it. Either bracket closes the current container, a mismatched one is
+    /// taken for a typo. Returns whether the container continues.
+    fn recover(&mut self, closing: TokenType<'a>) -> bool {
+        self.synchronize();
+        let Some(token) = self.expect() else {
+            return false;
+        };
+        match token.token_type {
+            TokenType::ValueSeparator => true,
+            _ if token.token_type == closing => false,
+            _ => {
+                let expected_tokens = match closing {
+                    TokenType::EndObject => vec![TokenKind::EndObject],
+                    _ => vec![TokenKind::EndArray],
+                };
+                self.unexpected(token, expected_tokens);
+                self.next();
+                false
+            }
+        }
+    }
+
+    /// Skips tokens up to a `,` or, inside a container, a closing bracket,
+    /// jumping over nested containers as a whole.
+    fn synchronize(&mut self) {
+        let is_nested = self.depth > 0;
+        loop {
+            match self.peek_type() {
+                None | Some(TokenType::ValueSeparator) => return,
+                Some(TokenType::EndObject | TokenType::EndArray) if is_nested => return,
+                Some(TokenType::BeginObject | TokenType::BeginArray) => {
+                    self.next();
+                    self.skip_nested(1);
+                }
+                Some(_) => {
+                    self.next();
+                }
+            }
+        }
+    }
+
+    /// Skips the rest of `depth` nested containers whose opening brackets
+    /// were already consumed.
+    fn skip_nested(&mut self, mut depth: usize) {
+        while depth > 0 {
+            let Some(token) = self.next() else {
+                return;
+            };
+            match token.token_type {
+                TokenType::BeginObject | TokenType::BeginArray => depth += 1,
+                TokenType::EndObject | TokenType::EndArray => depth -= 1,
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse, DuplicateKeyPolicy};
+    use test_case::test_case;
+
+    /// "line:column" of every error.
+    fn positions(errors: &[ParseError]) -> Vec<String> {
+        errors
+            .iter()
+            .map(|error| {
+                let location = error.location().unwrap();
+                format!("{}:{}", location.line, location.column)
+            })
+            .collect()
+    }
+
+    #[test_case(r#"{"a": [1, 2], "b": null}"#, r#"{"a": [1, 2], "b": null}"#, &[] ; "Valid document")]
+    #[test_case("[1 2, 3]", "[1, 3]", &["1:4"] ; "Missing comma")]
+    #[test_case("[1,,2]", "[1, null, 2]", &["1:4"] ; "Missing element")]
+    #[test_case("[tru, 01, 3]", "[null, null, 3]", &["1:2", "1:7"] ; "Invalid values")]
+    #[test_case("[1,]", "[1]", &["1:4"] ; "Trailing comma in array")]
+    #[test_case(r#"{"a": 1,}"#, r#"{"a": 1}"#, &["1:9"] ; "Trailing comma in object")]
+    #[test_case(r#"{"a" 1, "b": 2}"#, r#"{"b": 2}"#, &["1:6"] ; "Missing colon")]
+    #[test_case(r#"{a: 1, "b": }"#, r#"{"b": null}"#, &["1:2", "1:13"] ; "Broken members")]
+    #[test_case(r#"{"a": [1, 2}, "b": 3}"#, r#"{"a": [1, 2], "b": 3}"#, &["1:12"] ; "Unclosed array")]
+    #[test_case(r#"[{"a": 1], 2]"#, r#"[{"a": 1}, 2]"#, &["1:9"] ; "Unclosed object")]
+    #[test_case(r#"[1, : 2, {"a" [x]} z, 3]"#, "[1, null, {}, 3]", &["1:5", "1:15", "1:20"] ; "Skipped garbage")]
+    #[test_case(r#"{"a": [1, {"b": "#, r#"{"a": [1, {"b": null}]}"#, &["1:17"] ; "Truncated document")]
+    #[test_case("[1] 2", "[1]", &["1:5"] ; "Trailing content")]
+    #[test_case("", "null", &["1:1"] ; "Empty input")]
+    fn recovery(json: &str, expected: &str, expected_positions: &[&str]) {
+        let (value, errors) = parse_recovering(json);
+        assert_eq!(value, parse(expected).unwrap());
+        assert_eq!(positions(&errors), expected_positions);
+    }
+
+    #[test]
+    fn error_kinds() {
+        let (_, errors) = parse_recovering("[1 2, 01, tru, ");
+        assert!(matches!(
+            errors.as_slice(),
+            [
+                ParseError::UnexpectedToken(_),
+                ParseError::InvalidNumber { .. },
+                ParseError::InvalidToken(_),
+                ParseError::UnexpectedEndOfFile(_),
+            ]
+        ));
+    }
+
+    #[test]
+    fn options() {
+        let json = r#"{"a": [[[1]]], "a": 2, "b": 3}"#;
+        let options = ParseOptions::new()
+            .max_depth(2)
+            .duplicate_keys(DuplicateKeyPolicy::Error);
+        let (value, errors) = parse_recovering_with(json, options);
+        assert_eq!(value, parse(r#"{"a": [null], "b": 3}"#).unwrap());
+        assert_eq!(positions(&errors), ["1:8", "1:16"]);
+    }
+}
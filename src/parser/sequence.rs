@@ -0,0 +1,239 @@
+use super::{
+    options::ParseOptions,
+    parse_error::{ParseError, ParseErrorArgs, TokenKind},
+    parser::{next_token, next_value},
+    source::{ReadSource, Source, StrSource},
+    tokenizer::Tokenizer,
+    value::Value,
+};
+use std::{io::Read, mem};
+
+/// Iterator over several JSON values in one input, yielding each together
+/// with the byte offset it starts at.
+///
+/// By default the values simply follow one another, optionally separated by
+/// whitespace (`{"a":1}{"b":2}`), and iteration ends after the first error.
+/// [`record_separated`](ValueStream::record_separated) reads an RFC 7464
+/// JSON text sequence instead, where each value is preceded by a record
+/// separator (`0x1E`). A truncated or otherwise malformed record, as well as
+/// a value before the first record separator, is then reported and skipped,
+/// and iteration carries on with the next record.
+pub struct ValueStream<'a, S: Source<'a> = StrSource<'a>> {
+    tokenizer: Tokenizer<'a, S>,
+    record_separated: bool,
+    is_at_start: bool,
+    is_done: bool,
+}
+
+impl<'a> ValueStream<'a> {
+    pub fn new(json: &'a str) -> Self {
+        Self::with_options(json, ParseOptions::default())
+    }
+
+    pub fn with_options(json: &'a str, options: ParseOptions) -> Self {
+        Self::from_tokenizer(Tokenizer::with_options(json, options))
+    }
+}
+
+impl<R: Read> ValueStream<'static, ReadSource<R>> {
+    pub fn from_reader(reader: R) -> Self {
+        Self::from_reader_with(reader, ParseOptions::default())
+    }
+
+    pub fn from_reader_with(reader: R, options: ParseOptions) -> Self {
+        Self::from_tokenizer(Tokenizer::from_reader(reader, options))
+    }
+}
+
+impl<'a, S: Source<'a>> ValueStream<'a, S> {
+    fn from_tokenizer(tokenizer: Tokenizer<'a, S>) -> Self {
+        Self {
+            tokenizer,
+            record_separated: false,
+            is_at_start: true,
+            is_done: false,
+        }
+    }
+
+    /// Read an RFC 7464 JSON text sequence: every value is a record starting
+    /// with a record separator (`0x1E`), usually ending with a line feed.
+    pub fn record_separated(mut self, record_separated: bool) -> Self {
+        self.record_separated = record_separated;
+        self.tokenizer.record_separated(record_separated);
+        self
+    }
+
+    /// Byte offset right after the last value read.
+    pub fn byte_offset(&self) -> usize {
+        self.tokenizer.offset()
+    }
+
+    /// Parses the value of the current record, which has to be the only one
+    /// in it. A number or literal has to be followed by whitespace since it
+    /// could have been cut short otherwise, as RFC 7464 §2.4 demands.
+    fn record(&mut self) -> Result<Option<(Value, usize)>, ParseError> {
+        let Some((value, offset)) = next_value(&mut self.tokenizer)? else {
+            return Ok(None);
+        };
+        let is_scalar = matches!(value, Value::Null | Value::Bool(_) | Value::Number(_));
+        if is_scalar && !self.tokenizer.is_at_whitespace() {
+            return Err(ParseError::UnexpectedEndOfFile(
+                self.tokenizer.end_location(),
+            ));
+        }
+        if let Some(token) = next_token(&mut self.tokenizer)? {
+            return Err(ParseError::trailing_content(token));
+        }
+        Ok(Some((value, offset)))
+    }
+
+    /// Fails unless only whitespace precedes the first record separator.
+    fn preamble(&mut self) -> Result<(), ParseError> {
+        match next_token(&mut self.tokenizer)? {
+            Some(token) => Err(ParseError::UnexpectedToken(ParseErrorArgs::new(
+                token,
+                vec![TokenKind::RecordSeparator],
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    fn next_record(&mut self) -> Option<Result<(Value, usize), ParseError>> {
+        loop {
+            let result =
+                if mem::take(&mut self.is_at_start) && !self.tokenizer.skip_record_separator() {
+                    self.preamble().map(|()| None)
+                } else {
+                    self.record()
+                };
+            match result {
+                Ok(Some(value)) => return Some(Ok(value)),
+                Ok(None) => {}
+                Err(error) => {
+                    self.tokenizer.skip_record();
+                    self.is_done = !self.tokenizer.skip_record_separator();
+                    return Some(Err(error));
+                }
+            }
+            if !self.tokenizer.skip_record_separator() {
+                self.is_done = true;
+                return None;
+            }
+        }
+    }
+}
+
+impl<'a, S: Source<'a>> Iterator for ValueStream<'a, S> {
+    type Item = Result<(Value, usize), ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_done {
+            return None;
+        }
+        if self.record_separated {
+            return self.next_record();
+        }
+        let result = next_value(&mut self.tokenizer).transpose();
+        self.is_done = !matches!(result, Some(Ok(_)));
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse, ErrorKind, Location};
+    use test_case::test_case;
+
+    fn values(stream: ValueStream) -> Vec<Result<(Value, usize), ErrorKind>> {
+        stream
+            .map(|result| result.map_err(|error| error.kind()))
+            .collect()
+    }
+
+    fn expected(
+        expected: Vec<Result<(&str, usize), ErrorKind>>,
+    ) -> Vec<Result<(Value, usize), ErrorKind>> {
+        expected
+            .into_iter()
+            .map(|result| result.map(|(json, offset)| (parse(json).unwrap(), offset)))
+            .collect()
+    }
+
+    #[test_case(r#"{"a":1}{"b":2}"#, vec![Ok((r#"{"a":1}"#, 0)), Ok((r#"{"b":2}"#, 7))] ; "Back to back objects")]
+    #[test_case("1 2\n[3]\"4\"", vec![Ok(("1", 0)), Ok(("2", 2)), Ok(("[3]", 4)), Ok(("\"4\"", 7))] ; "Separated by whitespace")]
+    #[test_case("12", vec![Ok(("12", 0))] ; "Digits form one number")]
+    #[test_case(" \n ", vec![] ; "Only whitespace")]
+    #[test_case("[1] [2, } [3]", vec![Ok(("[1]", 0)), Err(ErrorKind::UnexpectedToken)] ; "Stops at error")]
+    #[test_case("{} {", vec![Ok(("{}", 0)), Err(ErrorKind::UnexpectedEndOfFile)] ; "Truncated last value")]
+    #[test_case("1 \u{1E}2", vec![Ok(("1", 0)), Err(ErrorKind::InvalidToken)] ; "Record separator without record mode")]
+    fn concatenated(json: &str, values_expected: Vec<Result<(&str, usize), ErrorKind>>) {
+        assert_eq!(values(ValueStream::new(json)), expected(values_expected));
+    }
+
+    #[test_case("\u{1E}{\"a\":1}\n\u{1E}[2]\n", vec![Ok((r#"{"a":1}"#, 1)), Ok(("[2]", 10))] ; "Records")]
+    #[test_case("\u{1E}{\"a\":\n\u{1E}[2]\n", vec![Err(ErrorKind::UnexpectedEndOfFile), Ok(("[2]", 8))] ; "Truncated object")]
+    #[test_case("\u{1E}\"abc\u{1E}true\n", vec![Err(ErrorKind::InvalidToken), Ok(("true", 6))] ; "Truncated string")]
+    #[test_case("\u{1E}12\u{1E}3\n", vec![Err(ErrorKind::UnexpectedEndOfFile), Ok(("3", 4))] ; "Number without whitespace")]
+    #[test_case("\u{1E}null", vec![Err(ErrorKind::UnexpectedEndOfFile)] ; "Literal at end of input")]
+    #[test_case("\u{1E}\"text\"", vec![Ok(("\"text\"", 1))] ; "String at end of input")]
+    #[test_case("\u{1E}1 2\n\u{1E}[}\n\u{1E}3\n", vec![Err(ErrorKind::TrailingContent), Err(ErrorKind::UnexpectedToken), Ok(("3", 10))] ; "Malformed records")]
+    #[test_case("\u{1E}\u{1E}\n\u{1E}1\n\u{1E}", vec![Ok(("1", 4))] ; "Empty records")]
+    #[test_case("1\n", vec![Err(ErrorKind::UnexpectedToken)] ; "Missing first separator")]
+    #[test_case("1\n\u{1E}2\n", vec![Err(ErrorKind::UnexpectedToken), Ok(("2", 3))] ; "Value before first separator")]
+    #[test_case(" \n\u{1E}2\n", vec![Ok(("2", 3))] ; "Whitespace before first separator")]
+    fn record_separated(json: &str, values_expected: Vec<Result<(&str, usize), ErrorKind>>) {
+        let stream = ValueStream::new(json).record_separated(true);
+        assert_eq!(values(stream), expected(values_expected));
+    }
+
+    #[test]
+    fn error_location() {
+        let json = "\u{1E}[1,\n\u{1E}[2,\n  3 4]\n";
+        let errors: Vec<_> = ValueStream::new(json)
+            .record_separated(true)
+            .filter_map(|result| result.err()?.location())
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                Location {
+                    line: 2,
+                    column: 1,
+                    length: 1,
+                    offset: 5,
+                },
+                Location {
+                    line: 3,
+                    column: 5,
+                    length: 1,
+                    offset: 14,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn byte_offset() {
+        let json = r#"{"a": 1} trailing"#;
+        let mut stream = ValueStream::new(json);
+        assert!(stream.next().unwrap().is_ok());
+        assert_eq!(&json[stream.byte_offset()..], " trailing");
+    }
+
+    #[test]
+    fn reader() {
+        let input = "\u{1E}{\"a\": [1,\n\u{1E}{\"b\": 2}\n".as_bytes();
+        let values: Vec<_> = ValueStream::from_reader(input)
+            .record_separated(true)
+            .map(|result| result.map_err(|error| error.kind()))
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                Err(ErrorKind::UnexpectedEndOfFile),
+                Ok((parse(r#"{"b": 2}"#).unwrap(), 12))
+            ]
+        );
+    }
+}
@@ -0,0 +1,319 @@
+use super::encoding::Encoding;
+use std::{
+    borrow::Cow,
+    io::{self, Read},
+    str,
+};
+
+/// Byte input consumed by the [`Tokenizer`](super::tokenizer::Tokenizer).
+/// Positions are absolute byte offsets from the start of the input.
+pub trait Source<'a> {
+    /// Byte `offset` bytes ahead of the current position.
+    fn peek(&mut self, offset: usize) -> Option<u8>;
+
+    /// Moves one byte forward.
+    fn advance(&mut self);
+
+    fn position(&self) -> usize;
+
+    /// Text from `start` up to the current position. `start` must not lie
+    /// before the last call to [`release`](Source::release).
+    fn text(&self, start: usize) -> Cow<'a, str>;
+
+    /// Signals that bytes before the current position are no longer needed.
+    fn release(&mut self) {}
+
+    /// Takes the I/O error that cut the input short, if any.
+    fn take_error(&mut self) -> Option<io::Error> {
+        None
+    }
+
+    /// The encoding of the input and the offset at which it stopped being
+    /// valid in it, where the input was cut short.
+    fn encoding_error(&self) -> Option<(Encoding, usize)> {
+        None
+    }
+}
+
+pub struct StrSource<'a> {
+    input: &'a str,
+    position: usize,
+    encoding_error: Option<(Encoding, usize)>,
+}
+
+impl<'a> StrSource<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            position: 0,
+            encoding_error: None,
+        }
+    }
+
+    /// Text decoded from bytes which turned invalid in `encoding` right
+    /// after `input`.
+    pub(crate) fn truncated(input: &'a str, encoding: Encoding) -> Self {
+        Self {
+            encoding_error: Some((encoding, input.len())),
+            ..Self::new(input)
+        }
+    }
+}
+
+impl<'a> Source<'a> for StrSource<'a> {
+    fn peek(&mut self, offset: usize) -> Option<u8> {
+        self.input.as_bytes().get(self.position + offset).copied()
+    }
+
+    fn advance(&mut self) {
+        self.position += 1;
+    }
+
+    fn position(&self) -> usize {
+        self.position
+    }
+
+    fn text(&self, start: usize) -> Cow<'a, str> {
+        Cow::Borrowed(&self.input[start..self.position])
+    }
+
+    fn encoding_error(&self) -> Option<(Encoding, usize)> {
+        self.encoding_error
+    }
+}
+
+pub const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Pulls input from a reader in chunks, keeping only the bytes of the token
+/// currently being read in memory. The input is validated as UTF-8 while
+/// reading and ends before the first invalid sequence.
+pub struct ReadSource<R: Read> {
+    reader: R,
+    buffer: Vec<u8>,
+    /// Absolute offset of `buffer[0]`.
+    buffer_offset: usize,
+    /// Index of the current byte in `buffer`.
+    index: usize,
+    /// Bytes of `buffer` before this index may be discarded.
+    released: usize,
+    /// Bytes of `buffer` before this index are valid UTF-8.
+    validated: usize,
+    is_exhausted: bool,
+    error: Option<io::Error>,
+    invalid_utf8: Option<usize>,
+}
+
+impl<R: Read> ReadSource<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buffer: Vec::new(),
+            buffer_offset: 0,
+            index: 0,
+            released: 0,
+            validated: 0,
+            is_exhausted: false,
+            error: None,
+            invalid_utf8: None,
+        }
+    }
+
+    /// Reads until the byte `offset` bytes ahead of the current one is
+    /// buffered or the reader is exhausted.
+    fn fill(&mut self, offset: usize) {
+        while self.validated <= self.index + offset && !self.is_exhausted {
+            if self.released > 0 {
+                self.buffer.drain(..self.released);
+                self.buffer_offset += self.released;
+                self.index -= self.released;
+                self.validated -= self.released;
+                self.released = 0;
+                continue;
+            }
+            let length = self.buffer.len();
+            self.buffer.resize(length + READ_CHUNK_SIZE, 0);
+            let result = self.reader.read(&mut self.buffer[length..]);
+            self.buffer
+                .truncate(length + *result.as_ref().unwrap_or(&0));
+            match result {
+                Ok(0) => self.is_exhausted = true,
+                Ok(_) => {}
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => {
+                    self.error = Some(error);
+                    self.is_exhausted = true;
+                }
+            }
+            self.validate();
+        }
+    }
+
+    /// Extends the validated part of the buffer, cutting the input short at
+    /// the first invalid UTF-8 sequence.
+    fn validate(&mut self) {
+        match str::from_utf8(&self.buffer[self.validated..]) {
+            Ok(_) => self.validated = self.buffer.len(),
+            Err(error) => {
+                self.validated += error.valid_up_to();
+                // An incomplete sequence at the end may be completed by the next read.
+                if error.error_len().is_some() || self.is_exhausted {
+                    self.invalid_utf8 = Some(self.buffer_offset + self.validated);
+                    self.buffer.truncate(self.validated);
+                    self.is_exhausted = true;
+                }
+            }
+        }
+    }
+}
+
+impl<R: Read> Source<'static> for ReadSource<R> {
+    fn peek(&mut self, offset: usize) -> Option<u8> {
+        self.fill(offset);
+        self.buffer[..self.validated]
+            .get(self.index + offset)
+            .copied()
+    }
+
+    fn advance(&mut self) {
+        self.index += 1;
+    }
+
+    fn position(&self) -> usize {
+        self.buffer_offset + self.index
+    }
+
+    fn text(&self, start: usize) -> Cow<'static, str> {
+        let bytes = &self.buffer[start - self.buffer_offset..self.index];
+        Cow::Owned(String::from_utf8_lossy(bytes).into_owned())
+    }
+
+    fn release(&mut self) {
+        self.released = self.index;
+    }
+
+    fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    fn encoding_error(&self) -> Option<(Encoding, usize)> {
+        self.invalid_utf8.map(|offset| (Encoding::Utf8, offset))
+    }
+}
+
+/// Input pushed in chunks by the [`IncrementalParser`](super::IncrementalParser).
+/// Reading beyond the input received so far marks the source as starved,
+/// unless the input is finished, so that the token being read can be
+/// retried once more input arrived.
+#[derive(Default)]
+pub struct ChunkSource {
+    buffer: Vec<u8>,
+    /// Absolute offset of `buffer[0]`.
+    buffer_offset: usize,
+    /// Index of the current byte in `buffer`.
+    index: usize,
+    /// Bytes of `buffer` before this index may be discarded.
+    committed: usize,
+    /// Bytes of `buffer` before this index are valid UTF-8.
+    validated: usize,
+    is_finished: bool,
+    is_starved: bool,
+    invalid_utf8: Option<usize>,
+}
+
+impl ChunkSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends `bytes` to the input, discarding what was committed.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        if self.is_finished || self.invalid_utf8.is_some() {
+            return;
+        }
+        self.buffer.drain(..self.committed);
+        self.buffer_offset += self.committed;
+        self.index -= self.committed;
+        self.validated -= self.committed;
+        self.committed = 0;
+        self.buffer.extend_from_slice(bytes);
+        self.validate();
+    }
+
+    /// Marks the end of input.
+    pub fn finish(&mut self) {
+        self.is_finished = true;
+        self.validate();
+    }
+
+    /// Whether reading ran out of input since the last call.
+    pub fn take_starved(&mut self) -> bool {
+        std::mem::take(&mut self.is_starved)
+    }
+
+    /// Signals that bytes before the current position will not be read again.
+    pub fn commit(&mut self) {
+        self.committed = self.index;
+    }
+
+    /// Goes back to `position`, which must not lie before the last commit.
+    pub fn rewind(&mut self, position: usize) {
+        self.index = position - self.buffer_offset;
+    }
+
+    /// The valid bytes received from `position` on, which must not lie
+    /// before the last commit.
+    pub fn received(&self, position: usize) -> &[u8] {
+        &self.buffer[position - self.buffer_offset..self.validated]
+    }
+
+    /// Whether no more input will arrive, because it is finished or was cut
+    /// short by invalid UTF-8.
+    pub fn is_complete(&self) -> bool {
+        self.is_finished || self.invalid_utf8.is_some()
+    }
+
+    /// Extends the validated part of the buffer, cutting the input short at
+    /// the first invalid UTF-8 sequence.
+    fn validate(&mut self) {
+        match str::from_utf8(&self.buffer[self.validated..]) {
+            Ok(_) => self.validated = self.buffer.len(),
+            Err(error) => {
+                self.validated += error.valid_up_to();
+                // An incomplete sequence at the end may be completed by the next chunk.
+                if error.error_len().is_some() || self.is_finished {
+                    self.invalid_utf8 = Some(self.buffer_offset + self.validated);
+                    self.buffer.truncate(self.validated);
+                }
+            }
+        }
+    }
+}
+
+impl Source<'static> for ChunkSource {
+    fn peek(&mut self, offset: usize) -> Option<u8> {
+        let byte = self.buffer[..self.validated]
+            .get(self.index + offset)
+            .copied();
+        if byte.is_none() && !self.is_complete() {
+            self.is_starved = true;
+        }
+        byte
+    }
+
+    fn advance(&mut self) {
+        self.index += 1;
+    }
+
+    fn position(&self) -> usize {
+        self.buffer_offset + self.index
+    }
+
+    fn text(&self, start: usize) -> Cow<'static, str> {
+        let bytes = &self.buffer[start - self.buffer_offset..self.index];
+        Cow::Owned(String::from_utf8_lossy(bytes).into_owned())
+    }
+
+    fn encoding_error(&self) -> Option<(Encoding, usize)> {
+        self.invalid_utf8.map(|offset| (Encoding::Utf8, offset))
+    }
+}
@@ -0,0 +1,297 @@
+use super::{
+    number::Number,
+    options::DuplicateKeyPolicy,
+    tokenizer::Location,
+    value::Value,
+    visitor::{insert, Collect, Visitor},
+};
+use indexmap::IndexMap;
+use std::{borrow::Cow, collections::HashSet, convert::Infallible, mem};
+
+/// A point in the input. Lines and columns start at 1, columns count
+/// characters and `offset` counts bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+    pub offset: usize,
+}
+
+impl From<Location> for Position {
+    fn from(location: Location) -> Self {
+        Self {
+            line: location.line,
+            column: location.column,
+            offset: location.offset,
+        }
+    }
+}
+
+/// The part of the input a value or key was parsed from, `end` being the
+/// position right behind its last character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spanned<T> {
+    pub value: T,
+    pub span: Span,
+}
+
+/// A [`Value`] whose every node and object key knows where it came from.
+#[derive(Debug, PartialEq)]
+pub enum SpannedValue {
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(String),
+    Object(IndexMap<String, Member>),
+    Array(Vec<Spanned<SpannedValue>>),
+}
+
+/// A property of a [`SpannedValue::Object`].
+#[derive(Debug, PartialEq)]
+pub struct Member {
+    pub key: Span,
+    pub value: Spanned<SpannedValue>,
+}
+
+impl Spanned<SpannedValue> {
+    /// Drops all spans.
+    pub fn into_value(self) -> Value {
+        match self.value {
+            SpannedValue::Null => Value::Null,
+            SpannedValue::Bool(bool) => Value::Bool(bool),
+            SpannedValue::Number(number) => Value::Number(number),
+            SpannedValue::String(string) => Value::String(string),
+            SpannedValue::Object(object) => Value::Object(
+                object
+                    .into_iter()
+                    .map(|(key, member)| (key, member.value.into_value()))
+                    .collect(),
+            ),
+            SpannedValue::Array(array) => {
+                Value::Array(array.into_iter().map(Self::into_value).collect())
+            }
+        }
+    }
+}
+
+enum Frame {
+    Object {
+        object: IndexMap<String, Member>,
+        /// The pending key and, once reported, its span.
+        key: Option<(String, Option<Span>)>,
+        /// Indices of members whose values were collected into an array.
+        collected: HashSet<usize>,
+    },
+    Array(Vec<Spanned<SpannedValue>>),
+}
+
+/// Visitor assembling a [`SpannedValue`] tree from the spans reported by
+/// [`Visitor::visit_span`].
+pub struct SpannedBuilder {
+    stack: Vec<Frame>,
+    /// The value visited last, waiting for its span.
+    pending: Option<SpannedValue>,
+    result: Option<Spanned<SpannedValue>>,
+    duplicate_keys: DuplicateKeyPolicy,
+}
+
+impl SpannedBuilder {
+    pub fn new(duplicate_keys: DuplicateKeyPolicy) -> Self {
+        Self {
+            stack: Vec::new(),
+            pending: None,
+            result: None,
+            duplicate_keys,
+        }
+    }
+
+    pub fn finish(self) -> Option<Spanned<SpannedValue>> {
+        self.result
+    }
+
+    fn push(&mut self, value: Spanned<SpannedValue>) {
+        match self.stack.last_mut() {
+            Some(Frame::Object {
+                object,
+                key,
+                collected,
+            }) => {
+                if let Some((key, Some(key_span))) = key.take() {
+                    let member = Member {
+                        key: key_span,
+                        value,
+                    };
+                    insert(object, collected, key, member, self.duplicate_keys);
+                }
+            }
+            Some(Frame::Array(array)) => array.push(value),
+            None => self.result = Some(value),
+        }
+    }
+}
+
+/// Collected values form an array spanning from the first to the last one.
+/// The member keeps the span of the first key.
+impl Collect for Member {
+    fn collect_first(&mut self, member: Self) {
+        let span = Span {
+            start: self.value.span.start,
+            end: member.value.span.end,
+        };
+        let placeholder = Spanned {
+            value: SpannedValue::Null,
+            span,
+        };
+        let first = mem::replace(&mut self.value, placeholder);
+        self.value.value = SpannedValue::Array(vec![first, member.value]);
+    }
+
+    fn collect(&mut self, member: Self) {
+        if let SpannedValue::Array(array) = &mut self.value.value {
+            self.value.span.end = member.value.span.end;
+            array.push(member.value);
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for SpannedBuilder {
+    type Error = Infallible;
+
+    fn visit_null(&mut self) -> Result<(), Self::Error> {
+        self.pending = Some(SpannedValue::Null);
+        Ok(())
+    }
+
+    fn visit_bool(&mut self, bool: bool) -> Result<(), Self::Error> {
+        self.pending = Some(SpannedValue::Bool(bool));
+        Ok(())
+    }
+
+    fn visit_number(&mut self, number: Number) -> Result<(), Self::Error> {
+        self.pending = Some(SpannedValue::Number(number));
+        Ok(())
+    }
+
+    fn visit_string(&mut self, string: Cow<'a, str>) -> Result<(), Self::Error> {
+        self.pending = Some(SpannedValue::String(string.into_owned()));
+        Ok(())
+    }
+
+    fn begin_object(&mut self) -> Result<(), Self::Error> {
+        self.stack.push(Frame::Object {
+            object: IndexMap::new(),
+            key: None,
+            collected: HashSet::new(),
+        });
+        Ok(())
+    }
+
+    fn visit_key(&mut self, key: Cow<'a, str>) -> Result<(), Self::Error> {
+        if let Some(Frame::Object { key: pending, .. }) = self.stack.last_mut() {
+            *pending = Some((key.into_owned(), None));
+        }
+        Ok(())
+    }
+
+    fn end_object(&mut self) -> Result<(), Self::Error> {
+        if let Some(Frame::Object { object, .. }) = self.stack.pop() {
+            self.pending = Some(SpannedValue::Object(object));
+        }
+        Ok(())
+    }
+
+    fn begin_array(&mut self) -> Result<(), Self::Error> {
+        self.stack.push(Frame::Array(Vec::new()));
+        Ok(())
+    }
+
+    fn end_array(&mut self) -> Result<(), Self::Error> {
+        if let Some(Frame::Array(array)) = self.stack.pop() {
+            self.pending = Some(SpannedValue::Array(array));
+        }
+        Ok(())
+    }
+
+    fn visit_span(&mut self, span: Span) -> Result<(), Self::Error> {
+        if let Some(value) = self.pending.take() {
+            self.push(Spanned { value, span });
+        } else if let Some(Frame::Object {
+            key: Some((_, key_span)),
+            ..
+        }) = self.stack.last_mut()
+        {
+            *key_span = Some(span);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse, parse_spanned, parse_spanned_with, ParseOptions};
+    use test_case::test_case;
+
+    /// (line, column, offset) of start and end.
+    fn positions(span: Span) -> ((usize, usize, usize), (usize, usize, usize)) {
+        let position = |position: Position| (position.line, position.column, position.offset);
+        (position(span.start), position(span.end))
+    }
+
+    #[test]
+    fn value_spans() {
+        let json = "{\n  \"name\": \"grüße\",\n  \"ports\": [80, {\"tls\": true}]\n}";
+        let spanned = parse_spanned(json).unwrap();
+        assert_eq!(positions(spanned.span), ((1, 1, 0), (4, 2, 55)));
+        let SpannedValue::Object(object) = &spanned.value else {
+            panic!("Expected object");
+        };
+        assert_eq!(positions(object["name"].key), ((2, 3, 4), (2, 9, 10)));
+        assert_eq!(
+            positions(object["name"].value.span),
+            ((2, 11, 12), (2, 18, 21))
+        );
+        let ports = &object["ports"];
+        assert_eq!(positions(ports.key), ((3, 3, 25), (3, 10, 32)));
+        assert_eq!(positions(ports.value.span), ((3, 12, 34), (3, 31, 53)));
+        let SpannedValue::Array(array) = &ports.value.value else {
+            panic!("Expected array");
+        };
+        assert_eq!(positions(array[0].span), ((3, 13, 35), (3, 15, 37)));
+        assert_eq!(positions(array[1].span), ((3, 17, 39), (3, 30, 52)));
+        assert_eq!(
+            &json[array[1].span.start.offset..array[1].span.end.offset],
+            "{\"tls\": true}"
+        );
+    }
+
+    #[test_case(r#"{"a": [1, {"b": null}], "c": "d"}"# ; "Nested containers")]
+    #[test_case("-1.5e3" ; "Scalar document")]
+    fn into_value(json: &str) {
+        assert_eq!(
+            parse_spanned(json).unwrap().into_value(),
+            parse(json).unwrap()
+        );
+    }
+
+    #[test_case(DuplicateKeyPolicy::FirstWins, (1, 7, 6), "[]" ; "First wins")]
+    #[test_case(DuplicateKeyPolicy::LastWins, (1, 25, 24), "2" ; "Last wins")]
+    #[test_case(DuplicateKeyPolicy::Collect, (1, 7, 6), "[[], {}, 2]" ; "Collect")]
+    fn duplicate_keys(policy: DuplicateKeyPolicy, start: (usize, usize, usize), expected: &str) {
+        let json = r#"{"a": [], "a": {}, "a": 2}"#;
+        let options = ParseOptions::new().duplicate_keys(policy);
+        let SpannedValue::Object(mut object) = parse_spanned_with(json, options).unwrap().value
+        else {
+            panic!("Expected object");
+        };
+        let member = object.swap_remove("a").unwrap();
+        assert_eq!(positions(member.value.span).0, start);
+        assert_eq!(member.value.into_value(), parse(expected).unwrap());
+    }
+}
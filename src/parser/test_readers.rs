@@ -0,0 +1,25 @@
+use std::io::{self, Read};
+
+/// Fails every read, like a connection that was reset.
+pub struct FailingReader;
+
+impl Read for FailingReader {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        Err(io::Error::other("connection reset"))
+    }
+}
+
+/// Hands out one byte per read, splitting every multi-byte character and
+/// exercising buffer boundaries.
+pub struct ByteReader<'a>(pub &'a [u8]);
+
+impl Read for ByteReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let Some((first, rest)) = self.0.split_first() else {
+            return Ok(0);
+        };
+        buf[0] = *first;
+        self.0 = rest;
+        Ok(1)
+    }
+}
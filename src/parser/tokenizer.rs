@@ -1,7 +1,20 @@
-use super::number::Number;
+use super::{
+    encoding::Encoding,
+    number::{Number, NumberError},
+    options::{Dialect, ParseOptions},
+    source::{ReadSource, Source, StrSource},
+};
+use std::{
+    borrow::Cow,
+    io::{self, Read},
+};
 
-#[derive(Debug, PartialEq, PartialOrd)]
-pub enum TokenType {
+const BYTE_ORDER_MARK: [u8; 3] = [0xEF, 0xBB, 0xBF];
+/// Starts each record of an RFC 7464 JSON text sequence.
+const RECORD_SEPARATOR: u8 = 0x1E;
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum TokenType<'a> {
     BeginArray,
     EndArray,
 
@@ -16,253 +29,888 @@ pub enum TokenType {
     Null,
 
     Number(Number),
-    String(String),
+    /// Borrows from the input unless escape sequences had to be decoded.
+    String(Cow<'a, str>),
+    /// An unquoted JSON5 key other than a literal.
+    Identifier(Cow<'a, str>),
 
     Invalid,
+    InvalidNumber(NumberError, Location),
+    /// A string violating the string grammar at the given location.
+    InvalidString(StringError, Location),
+    /// A string exceeding the maximum length, cut short at the given location.
+    StringTooLong(Location),
+    /// A block comment running until the end of input.
+    UnterminatedComment,
+}
+
+/// Violations of the string grammar detected by the tokenizer.
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy)]
+pub enum StringError {
+    /// A raw character below U+0020, or in JSON5 a raw line break.
+    ControlCharacter(u8),
+    /// An escaped UTF-16 surrogate without its other half.
+    LoneSurrogate(u16),
+    InvalidEscape,
 }
 
-#[derive(Debug, PartialEq, PartialOrd)]
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy)]
 pub struct Location {
     pub line: usize,
     pub column: usize,
+    /// Length in characters.
     pub length: usize,
+    /// Byte offset of the first character in the input.
+    pub offset: usize,
 }
 
-impl Location {
-    fn from_tokenizer(tokenizer: &Tokenizer, column_start: &usize) -> Self {
-        Self {
-            line: tokenizer.line,
-            column: *column_start,
-            length: tokenizer.column - *column_start,
-        }
-    }
-}
-
-#[derive(Debug, PartialEq, PartialOrd)]
-pub struct Token {
-    pub token_type: TokenType,
-    pub value: String,
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct Token<'a> {
+    pub token_type: TokenType<'a>,
+    pub value: Cow<'a, str>,
     pub location: Location,
 }
 
-const EOF: char = '\u{0}';
+/// States of the number lexer, named after the part of
+/// `[ minus ] int [ frac ] [ exp ]` that was read last. JSON5 adds a `+` sign,
+/// a decimal point without integer digits and hexadecimal integers.
+#[derive(Clone, Copy, PartialEq)]
+enum NumberState {
+    Start,
+    Minus,
+    Zero,
+    Integer,
+    LeadingDecimalPoint,
+    DecimalPoint,
+    Fraction,
+    ExponentMarker,
+    ExponentSign,
+    Exponent,
+    HexMarker,
+    HexDigits,
+}
 
-pub struct Tokenizer {
-    character: char,
-    characters: Vec<char>,
-    pub position: usize,
+/// Splits the input into tokens by scanning its UTF-8 bytes. Columns count
+/// characters, whereas [`offset`](Tokenizer::offset) is a byte offset.
+pub struct Tokenizer<'a, S: Source<'a> = StrSource<'a>> {
+    source: S,
     pub line: usize,
     pub column: usize,
+    options: ParseOptions,
+    /// Set once reading stopped at the maximum input size.
+    input_too_large: bool,
+    /// Set once reading stopped at input invalid in its encoding.
+    encoding_error: Option<(Encoding, usize)>,
+    /// Whether record separators end the input like in an RFC 7464 JSON
+    /// text sequence.
+    record_separated: bool,
+    _input: std::marker::PhantomData<&'a str>,
+}
+
+impl<'a> Tokenizer<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self::with_options(input, ParseOptions::default())
+    }
+
+    pub fn with_options(input: &'a str, options: ParseOptions) -> Self {
+        Self::from_source(StrSource::new(input), options)
+    }
 }
 
-impl Tokenizer {
-    pub fn new(input: String) -> Self {
-        let characters = input.chars().collect::<Vec<char>>();
+impl<R: Read> Tokenizer<'static, ReadSource<R>> {
+    pub fn from_reader(reader: R, options: ParseOptions) -> Self {
+        Self::from_source(ReadSource::new(reader), options)
+    }
+}
+
+impl<'a, S: Source<'a>> Tokenizer<'a, S> {
+    /// A leading UTF-8 byte order mark is skipped without counting a column.
+    pub fn from_source(source: S, options: ParseOptions) -> Self {
+        let mut tokenizer = Self::without_byte_order_mark(source, options);
+        tokenizer.skip_byte_order_mark();
+        tokenizer
+    }
+
+    /// Like [`from_source`](Tokenizer::from_source), but leaves skipping the
+    /// byte order mark to the caller, for input that is yet to arrive.
+    pub fn without_byte_order_mark(source: S, options: ParseOptions) -> Self {
         Self {
-            character: characters[0],
-            characters,
-            position: 0,
+            source,
             line: 1,
             column: 1,
+            options,
+            input_too_large: false,
+            encoding_error: None,
+            record_separated: false,
+            _input: std::marker::PhantomData,
         }
     }
 
-    fn read_char(&mut self) {
-        self.position = self.position + 1;
-        self.column = self.column + 1;
-        let next_character = self.characters.get(self.position);
-        if let Some(next_character) = next_character {
-            self.character = *next_character;
-        } else {
-            self.character = EOF;
+    /// Skips a UTF-8 byte order mark at the current position.
+    pub fn skip_byte_order_mark(&mut self) {
+        if (0..3)
+            .map(|offset| self.source.peek(offset))
+            .eq(BYTE_ORDER_MARK.map(Some))
+        {
+            for _ in 0..3 {
+                self.source.advance();
+            }
+        }
+    }
+
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    pub fn source_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    /// Byte offset of the next unread character.
+    pub fn offset(&self) -> usize {
+        self.source.position()
+    }
+
+    pub fn options(&self) -> &ParseOptions {
+        &self.options
+    }
+
+    /// Takes the I/O error that ended a reader's input prematurely.
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.source.take_error()
+    }
+
+    /// Whether the input was cut off at the maximum input size.
+    pub fn is_input_too_large(&self) -> bool {
+        self.input_too_large
+    }
+
+    /// Where the input was cut off because it is invalid in its encoding.
+    pub fn encoding_error(&self) -> Option<(Encoding, Location)> {
+        let (encoding, offset) = self.encoding_error?;
+        // Only ASCII lies between the current position and the lookahead
+        // that ran into the invalid input.
+        let location = Location {
+            line: self.line,
+            column: self.column + offset.saturating_sub(self.offset()),
+            length: 1,
+            offset,
         };
+        Some((encoding, location))
     }
 
-    fn read_chars(&mut self, number: usize) {
-        for _ in 0..number {
-            self.read_char();
+    /// Treat a record separator like the end of input, until it is skipped
+    /// with [`skip_record_separator`](Tokenizer::skip_record_separator).
+    pub fn record_separated(&mut self, record_separated: bool) {
+        self.record_separated = record_separated;
+    }
+
+    /// Reads the record separator at the current position, if there is one.
+    pub fn skip_record_separator(&mut self) -> bool {
+        if !self.record_separated || self.source.peek(0) != Some(RECORD_SEPARATOR) {
+            return false;
         }
+        self.read_byte();
+        self.source.release();
+        true
     }
 
-    fn peak_char(&self, offset: usize) -> char {
-        if let Some(next_character) = self.characters.get(self.position + offset) {
-            *next_character
-        } else {
-            EOF
+    /// Skips the rest of the current record, up to the next record separator
+    /// or the end of input.
+    pub fn skip_record(&mut self) {
+        while let Some(byte) = self.current() {
+            match byte {
+                b'\n' => self.advance_line(),
+                b'\r' if self.peek_byte(1) != Some(b'\n') => self.advance_line(),
+                _ => {}
+            }
+            self.read_byte();
+            self.source.release();
+        }
+    }
+
+    /// Whether the next byte is whitespace, as opposed to another token or
+    /// the end of input.
+    pub fn is_at_whitespace(&mut self) -> bool {
+        matches!(self.current(), Some(b' ' | b'\t' | b'\n' | b'\r'))
+    }
+
+    fn current(&mut self) -> Option<u8> {
+        self.peek_byte(0)
+    }
+
+    /// Input beyond the maximum input size, or a record separator if the
+    /// input is record separated, is treated like the end of input.
+    fn peek_byte(&mut self, offset: usize) -> Option<u8> {
+        let Some(byte) = self.source.peek(offset) else {
+            if self.encoding_error.is_none() {
+                self.encoding_error = self.source.encoding_error();
+            }
+            return None;
+        };
+        if self.record_separated
+            && (0..=offset).any(|ahead| self.source.peek(ahead) == Some(RECORD_SEPARATOR))
+        {
+            return None;
+        }
+        if self.offset() + offset >= self.options.max_input_size {
+            self.input_too_large = true;
+            return None;
+        }
+        Some(byte)
+    }
+
+    fn read_byte(&mut self) {
+        // Continuation bytes belong to the character whose column was already
+        // counted. Checking the byte read rather than the next one avoids
+        // looking beyond the end of the input received so far.
+        if !self.current().is_some_and(is_continuation_byte) {
+            self.column += 1;
+        }
+        self.source.advance();
+    }
+
+    fn read_char(&mut self) {
+        let width = self.current().map_or(1, utf8_width);
+        for _ in 0..width {
+            self.read_byte();
         }
     }
 
     fn advance_line(&mut self) {
-        self.line = self.line + 1;
+        self.line += 1;
         self.column = 0;
     }
 
-    fn skip_whitespace(&mut self) {
+    fn is_json5(&self) -> bool {
+        self.options.dialect == Dialect::Json5
+    }
+
+    /// Decodes the character starting at the current byte.
+    fn peek_char(&mut self) -> Option<char> {
+        let width = utf8_width(self.current()?);
+        let mut bytes = [0; 4];
+        for (offset, byte) in bytes[..width].iter_mut().enumerate() {
+            *byte = self.peek_byte(offset)?;
+        }
+        std::str::from_utf8(&bytes[..width]).ok()?.chars().next()
+    }
+
+    /// Skips whitespace and, if the dialect allows them, comments. A block
+    /// comment running until the end of input is returned as a token.
+    fn skip_whitespace(&mut self) -> Option<Token<'a>> {
         loop {
-            match self.character {
-                ' ' => {}
-                '\n' => self.advance_line(),
-                '\r' => self.advance_line(),
-                '\t' => {}
+            self.source.release();
+            match self.current() {
+                Some(b' ' | b'\t') => {}
+                Some(b'\n') => self.advance_line(),
+                Some(b'\r') if self.peek_byte(1) != Some(b'\n') => self.advance_line(),
+                Some(b'\r') => {}
+                Some(b'/') if self.options.dialect.allows_comments() => match self.peek_byte(1) {
+                    Some(b'/') => {
+                        self.skip_sequence(|byte| !matches!(byte, b'\n' | b'\r'));
+                        continue;
+                    }
+                    Some(b'*') => match self.skip_block_comment() {
+                        Some(unterminated_comment) => return Some(unterminated_comment),
+                        None => continue,
+                    },
+                    _ => break,
+                },
+                Some(0x0B | 0x0C) if self.is_json5() => {}
+                Some(0x80..) if self.is_json5() => match self.peek_char() {
+                    Some(character) if is_json5_whitespace(character) => {
+                        if is_line_separator(character) {
+                            self.advance_line();
+                        }
+                        self.read_char();
+                        continue;
+                    }
+                    _ => break,
+                },
                 _ => break,
             };
-            self.read_char();
+            self.read_byte();
         }
+        None
     }
 
-    fn read_sequence(&mut self, is_valid: fn(character: char) -> bool) -> (usize, String) {
-        let column_start = self.column;
-        let mut sequence = String::new();
+    /// Skips a `/* */` comment, returning `None` when it is closed properly.
+    fn skip_block_comment(&mut self) -> Option<Token<'a>> {
+        let location = Location {
+            line: self.line,
+            column: self.column,
+            length: 2,
+            offset: self.offset(),
+        };
+        self.read_byte();
+        self.read_byte();
         loop {
-            if !is_valid(self.character) {
-                break;
+            match self.current() {
+                Some(b'*') if self.peek_byte(1) == Some(b'/') => {
+                    self.read_byte();
+                    self.read_byte();
+                    return None;
+                }
+                Some(b'\n') => self.advance_line(),
+                Some(b'\r') if self.peek_byte(1) != Some(b'\n') => self.advance_line(),
+                Some(_) => {}
+                None => {
+                    return Some(Token {
+                        token_type: TokenType::UnterminatedComment,
+                        value: Cow::Borrowed("/*"),
+                        location,
+                    })
+                }
             }
-            sequence.push(self.character);
             self.read_char();
         }
-        (column_start, sequence)
     }
 
-    fn read_literal(&mut self) -> Token {
-        let (column_start, literal) = self.read_sequence(is_letter);
-        let token_type = match literal.as_str() {
+    fn location_from(&self, offset_start: usize, column_start: usize) -> Location {
+        Location {
+            line: self.line,
+            column: column_start,
+            length: self.column - column_start,
+            offset: offset_start,
+        }
+    }
+
+    fn token_from(
+        &self,
+        token_type: TokenType<'a>,
+        offset_start: usize,
+        column_start: usize,
+    ) -> Token<'a> {
+        Token {
+            token_type,
+            value: self.source.text(offset_start),
+            location: self.location_from(offset_start, column_start),
+        }
+    }
+
+    fn skip_sequence(&mut self, is_valid: fn(byte: u8) -> bool) {
+        while self.current().is_some_and(is_valid) {
+            self.read_byte();
+        }
+    }
+
+    /// Reads a JSON5 identifier, which may also be one of the literals
+    /// including `Infinity` and `NaN`.
+    fn read_identifier(&mut self) -> Token<'a> {
+        let (offset_start, column_start) = (self.offset(), self.column);
+        // Only allocated once the first escape sequence is encountered.
+        let mut decoded: Option<String> = None;
+        let mut chunk_start = self.offset();
+        loop {
+            let is_start = self.offset() == offset_start;
+            match self.current() {
+                Some(b'\\') if self.peek_byte(1) == Some(b'u') => {
+                    let decoded = decoded.get_or_insert_with(String::new);
+                    decoded.push_str(&self.source.text(chunk_start));
+                    self.read_byte();
+                    match self.read_unicode_escape_sequence() {
+                        Ok(character) if is_identifier_character(character, is_start) => {
+                            decoded.push(character)
+                        }
+                        _ => {
+                            return self.token_from(TokenType::Invalid, offset_start, column_start)
+                        }
+                    }
+                    self.read_byte();
+                    chunk_start = self.offset();
+                }
+                Some(_) => match self.peek_char() {
+                    Some(character) if is_identifier_character(character, is_start) => {
+                        self.read_char()
+                    }
+                    _ => break,
+                },
+                None => break,
+            }
+        }
+        if self.offset() == offset_start {
+            return self.read_plain_token(TokenType::Invalid);
+        }
+        let token_type = match decoded {
+            Some(mut decoded) => {
+                decoded.push_str(&self.source.text(chunk_start));
+                TokenType::Identifier(Cow::Owned(decoded))
+            }
+            None => match self.source.text(offset_start) {
+                name if name == "true" => TokenType::True,
+                name if name == "false" => TokenType::False,
+                name if name == "null" => TokenType::Null,
+                name if name == "Infinity" => TokenType::Number(Number::Float(f64::INFINITY)),
+                name if name == "NaN" => TokenType::Number(Number::Float(f64::NAN)),
+                name => TokenType::Identifier(name),
+            },
+        };
+        self.token_from(token_type, offset_start, column_start)
+    }
+
+    fn read_literal(&mut self) -> Token<'a> {
+        let (offset_start, column_start) = (self.offset(), self.column);
+        self.skip_sequence(is_letter);
+        let token_type = match self.source.text(offset_start).as_ref() {
             "true" => TokenType::True,
             "false" => TokenType::False,
             "null" => TokenType::Null,
             _ => TokenType::Invalid,
         };
-        Token {
-            token_type,
-            value: literal,
-            location: Location::from_tokenizer(&self, &column_start),
-        }
+        self.token_from(token_type, offset_start, column_start)
+    }
+
+    fn next_number_state(
+        &mut self,
+        state: NumberState,
+    ) -> Result<Option<NumberState>, NumberError> {
+        let json5 = self.is_json5();
+        let next_state = match (state, self.current()) {
+            (NumberState::Start, Some(b'-')) => NumberState::Minus,
+            (NumberState::Start, Some(b'+')) if json5 => NumberState::Minus,
+            (NumberState::Start, Some(b'+')) => return Err(NumberError::LeadingPlus),
+            (NumberState::Start | NumberState::Minus, Some(b'0')) => NumberState::Zero,
+            (NumberState::Start | NumberState::Minus, Some(b'1'..=b'9')) => NumberState::Integer,
+            (NumberState::Start | NumberState::Minus, Some(b'.')) if json5 => {
+                NumberState::LeadingDecimalPoint
+            }
+            (NumberState::Start | NumberState::Minus, _) => {
+                return Err(NumberError::MissingIntegerDigits)
+            }
+            (NumberState::Zero, Some(b'x' | b'X')) if json5 => NumberState::HexMarker,
+            (NumberState::HexMarker | NumberState::HexDigits, Some(byte))
+                if byte.is_ascii_hexdigit() =>
+            {
+                NumberState::HexDigits
+            }
+            (NumberState::HexMarker, _) => return Err(NumberError::MissingHexDigits),
+            (NumberState::Zero, Some(b'0'..=b'9')) => return Err(NumberError::LeadingZero),
+            (NumberState::Integer, Some(b'0'..=b'9')) => NumberState::Integer,
+            (NumberState::Zero | NumberState::Integer, Some(b'.')) => NumberState::DecimalPoint,
+            (
+                NumberState::LeadingDecimalPoint
+                | NumberState::DecimalPoint
+                | NumberState::Fraction,
+                Some(b'0'..=b'9'),
+            ) => NumberState::Fraction,
+            (NumberState::DecimalPoint, Some(b'e' | b'E')) if json5 => NumberState::ExponentMarker,
+            (NumberState::DecimalPoint, _) if json5 => return Ok(None),
+            (NumberState::LeadingDecimalPoint | NumberState::DecimalPoint, _) => {
+                return Err(NumberError::MissingFractionDigits)
+            }
+            (
+                NumberState::Zero | NumberState::Integer | NumberState::Fraction,
+                Some(b'e' | b'E'),
+            ) => NumberState::ExponentMarker,
+            (NumberState::ExponentMarker, Some(b'-' | b'+')) => NumberState::ExponentSign,
+            (
+                NumberState::ExponentMarker | NumberState::ExponentSign | NumberState::Exponent,
+                Some(b'0'..=b'9'),
+            ) => NumberState::Exponent,
+            (NumberState::ExponentMarker | NumberState::ExponentSign, _) => {
+                return Err(NumberError::MissingExponentDigits)
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(next_state))
+    }
+
+    /// Reads `Infinity` or `NaN` preceded by a sign.
+    fn read_signed_literal(&mut self) -> Token<'a> {
+        let (offset_start, column_start) = (self.offset(), self.column);
+        let sign = if self.current() == Some(b'-') {
+            -1.0
+        } else {
+            1.0
+        };
+        self.read_byte();
+        self.skip_sequence(is_letter);
+        let token_type = match &self.source.text(offset_start)[1..] {
+            "Infinity" => TokenType::Number(Number::Float(sign * f64::INFINITY)),
+            "NaN" => TokenType::Number(Number::Float(f64::NAN)),
+            _ => TokenType::Invalid,
+        };
+        self.token_from(token_type, offset_start, column_start)
     }
 
-    fn read_number(&mut self) -> Token {
-        let (column_start, sequence) = self.read_sequence(is_number_character);
-        let parsed_value = Number::parse(&sequence);
-        let token_type = if let Some(parsed_value) = parsed_value {
+    fn read_number(&mut self) -> Token<'a> {
+        let (offset_start, column_start) = (self.offset(), self.column);
+        if self.is_json5()
+            && matches!(self.current(), Some(b'-' | b'+'))
+            && self.peek_byte(1).is_some_and(is_letter)
+        {
+            return self.read_signed_literal();
+        }
+        let mut state = NumberState::Start;
+        let error = loop {
+            match self.next_number_state(state) {
+                Ok(Some(next_state)) => state = next_state,
+                Ok(None) => break None,
+                Err(error) => break Some(error),
+            }
+            self.read_byte();
+        };
+        let token_type = if let Some(error) = error {
+            let error_location = match error {
+                NumberError::LeadingZero => Location {
+                    line: self.line,
+                    column: self.column - 1,
+                    length: 1,
+                    offset: self.offset() - 1,
+                },
+                _ => Location {
+                    line: self.line,
+                    column: self.column,
+                    length: 1,
+                    offset: self.offset(),
+                },
+            };
+            // Swallow the rest of the malformed number so it is reported as a whole.
+            self.skip_sequence(is_number_character);
+            TokenType::InvalidNumber(error, error_location)
+        } else if state == NumberState::HexDigits {
+            TokenType::Number(hex_number(&self.source.text(offset_start)))
+        } else if self.is_json5() {
+            let lexeme = to_json_number(&self.source.text(offset_start));
+            if self.options.lossless_numbers {
+                TokenType::Number(Number::Raw(lexeme))
+            } else if let Some(parsed_value) = Number::parse(&lexeme) {
+                TokenType::Number(parsed_value)
+            } else {
+                TokenType::Invalid
+            }
+        } else if self.options.lossless_numbers {
+            TokenType::Number(Number::Raw(self.source.text(offset_start).into_owned()))
+        } else if let Some(parsed_value) = Number::parse(&self.source.text(offset_start)) {
             TokenType::Number(parsed_value)
         } else {
             TokenType::Invalid
         };
-        Token {
-            token_type,
-            value: sequence,
-            location: Location::from_tokenizer(&self, &column_start),
+        self.token_from(token_type, offset_start, column_start)
+    }
+
+    fn read_hex_sequence(&mut self, digits: usize) -> Option<u32> {
+        let mut code_unit = 0;
+        for _ in 0..digits {
+            self.read_byte();
+            let digit = (self.current()? as char).to_digit(16)?;
+            code_unit = code_unit * 16 + digit;
         }
+        Some(code_unit)
     }
 
-    fn read_hex_sequence(&mut self) -> Option<String> {
-        let mut sequence = String::with_capacity(4);
-        for _ in 0..4 {
-            self.read_char();
-            if !is_hex_character(self.character) {
-                return None;
+    fn read_unicode_escape_sequence(&mut self) -> Result<char, StringError> {
+        let code_unit = self
+            .read_hex_sequence(4)
+            .ok_or(StringError::InvalidEscape)?;
+        let code_point = match code_unit {
+            0xD800..=0xDBFF => {
+                let Some(low_surrogate) = self.peek_low_surrogate() else {
+                    return self.lone_surrogate(code_unit);
+                };
+                for _ in 0..6 {
+                    self.read_byte();
+                }
+                0x10000 + ((code_unit - 0xD800) << 10) + (low_surrogate - 0xDC00)
             }
-            sequence.push(self.character);
+            0xDC00..=0xDFFF => return self.lone_surrogate(code_unit),
+            _ => code_unit,
+        };
+        Ok(char::from_u32(code_point).expect("surrogates were paired"))
+    }
+
+    /// The low surrogate escaped right after the current character, if any.
+    fn peek_low_surrogate(&mut self) -> Option<u32> {
+        if self.peek_byte(1) != Some(b'\\') || self.peek_byte(2) != Some(b'u') {
+            return None;
         }
-        Some(sequence)
-    }
-
-    fn read_unicode_escape_sequence(&mut self) -> Option<char> {
-        let unicode_sequence = self.read_hex_sequence()?;
-        let mut code_point = u32::from_str_radix(&unicode_sequence, 16).ok()?;
-        let is_expecting_surrogate_pair = code_point >= 0xD800
-            && code_point <= 0xDBFF
-            && self.peak_char(1) == '\\'
-            && self.peak_char(2) == 'u';
-        if is_expecting_surrogate_pair {
-            self.read_chars(2);
-            let surrogate_sequence = self.read_hex_sequence()?;
-            let high_surrogate = code_point;
-            let low_surrogate = u32::from_str_radix(&surrogate_sequence, 16).ok()?;
-            code_point = 0x10000 + ((high_surrogate - 0xD800) << 10) + (low_surrogate - 0xDC00);
+        let mut code_unit = 0;
+        for offset in 3..7 {
+            code_unit = code_unit * 16 + (self.peek_byte(offset)? as char).to_digit(16)?;
         }
-        char::from_u32(code_point)
+        (0xDC00..=0xDFFF).contains(&code_unit).then_some(code_unit)
     }
 
-    fn read_escape_sequence(&mut self) -> Option<char> {
-        self.read_char();
-        match self.character {
-            '"' => Some('"'),
-            '\\' => Some('\\'),
-            '/' => Some('/'),
-            'b' => Some('\u{8}'),
-            'f' => Some('\u{c}'),
-            'n' => Some('\n'),
-            'r' => Some('\r'),
-            't' => Some('\t'),
-            'u' => self.read_unicode_escape_sequence(),
-            _ => None,
+    fn lone_surrogate(&self, code_unit: u32) -> Result<char, StringError> {
+        if self.options.replace_lone_surrogates {
+            Ok(char::REPLACEMENT_CHARACTER)
+        } else {
+            Err(StringError::LoneSurrogate(code_unit as u16))
         }
     }
 
-    fn read_string(&mut self) -> Token {
-        let position_start = self.position;
-        let column_start = self.column;
-        let mut sequence = Vec::<char>::new();
-        loop {
-            let should_break = sequence.len() >= 1 && self.character == '"';
-            let maybe_character = match self.character {
-                '\\' => self.read_escape_sequence(),
-                EOF => None,
-                _ => Some(self.character),
-            };
-            let Some(character) = maybe_character else {
-                return Token {
-                    token_type: TokenType::Invalid,
-                    value: sequence.iter().collect(),
-                    location: Location::from_tokenizer(&self, &column_start),
-                };
-            };
-            sequence.push(character);
-            self.read_char();
-            if should_break {
-                break;
+    fn read_escape_sequence(&mut self) -> Result<char, StringError> {
+        self.read_byte();
+        let character = match self.current() {
+            Some(b'"') => Some('"'),
+            Some(b'\\') => Some('\\'),
+            Some(b'/') => Some('/'),
+            Some(b'b') => Some('\u{8}'),
+            Some(b'f') => Some('\u{c}'),
+            Some(b'n') => Some('\n'),
+            Some(b'r') => Some('\r'),
+            Some(b't') => Some('\t'),
+            Some(b'u') => return self.read_unicode_escape_sequence(),
+            Some(_) if self.is_json5() => self.read_json5_escape_sequence(),
+            _ => None,
+        };
+        character.ok_or(StringError::InvalidEscape)
+    }
+
+    /// Escapes JSON5 adds to those of JSON: `\v`, `\0`, `\xFF` and any other
+    /// character apart from digits standing for itself.
+    fn read_json5_escape_sequence(&mut self) -> Option<char> {
+        match self.current()? {
+            b'v' => Some('\u{b}'),
+            b'0' if !self.peek_byte(1).is_some_and(|byte| byte.is_ascii_digit()) => Some('\0'),
+            b'x' => char::from_u32(self.read_hex_sequence(2)?),
+            b'0'..=b'9' | b'\n' | b'\r' => None,
+            _ => {
+                let character = self.peek_char()?;
+                for _ in 1..character.len_utf8() {
+                    self.read_byte();
+                }
+                Some(character)
             }
         }
-        let string_value = sequence[1..(sequence.len() - 1)].iter().collect();
-        let original_sequence = self.characters[position_start..self.position]
-            .iter()
-            .collect();
-        Token {
-            token_type: TokenType::String(string_value),
-            value: original_sequence,
-            location: Location::from_tokenizer(&self, &column_start),
+    }
+
+    /// Skips a backslash followed by a line terminator, with which JSON5
+    /// strings continue on the next line.
+    fn skip_line_continuation(&mut self) -> bool {
+        let width = match (self.peek_byte(1), self.peek_byte(2), self.peek_byte(3)) {
+            (Some(b'\r'), Some(b'\n'), _) => 2,
+            (Some(b'\n' | b'\r'), _, _) => 1,
+            (Some(0xE2), Some(0x80), Some(0xA8 | 0xA9)) => 3,
+            _ => return false,
+        };
+        for _ in 0..width {
+            self.read_byte();
         }
+        self.advance_line();
+        self.read_byte();
+        true
     }
 
-    fn read_plain_token(&mut self, token_type: TokenType) -> Token {
-        let token = Token {
+    /// Like [`token_from`](Self::token_from), but for tokens which may span
+    /// several lines, namely JSON5 strings with line continuations.
+    fn multiline_token_from(
+        &self,
+        token_type: TokenType<'a>,
+        offset_start: usize,
+        column_start: usize,
+        line_start: usize,
+    ) -> Token<'a> {
+        if self.line == line_start {
+            return self.token_from(token_type, offset_start, column_start);
+        }
+        let value = self.source.text(offset_start);
+        Token {
             token_type,
-            value: self.character.into(),
             location: Location {
-                line: self.line,
-                column: self.column,
-                length: 1,
+                line: line_start,
+                column: column_start,
+                length: value.chars().count(),
+                offset: offset_start,
             },
+            value,
+        }
+    }
+
+    fn read_string(&mut self, quote: u8) -> Token<'a> {
+        let (offset_start, column_start, line_start) = (self.offset(), self.column, self.line);
+        let invalid = |tokenizer: &Self| {
+            tokenizer.multiline_token_from(
+                TokenType::Invalid,
+                offset_start,
+                column_start,
+                line_start,
+            )
+        };
+        self.read_byte();
+        // Only allocated once the first escape sequence is encountered.
+        let mut decoded: Option<String> = None;
+        let mut chunk_start = self.offset();
+        loop {
+            let length = decoded.as_ref().map_or(0, String::len) + self.offset() - chunk_start;
+            match self.current() {
+                Some(byte) if byte == quote => break,
+                Some(byte @ 0x00..=0x1F) if !self.is_json5() || matches!(byte, b'\n' | b'\r') => {
+                    let location = Location {
+                        length: 1,
+                        ..self.location_from(self.offset(), self.column)
+                    };
+                    let error = StringError::ControlCharacter(byte);
+                    return self.invalid_string(
+                        error,
+                        location,
+                        quote,
+                        offset_start,
+                        column_start,
+                        line_start,
+                    );
+                }
+                Some(byte) if length + utf8_width(byte) > self.options.max_string_length => {
+                    let character_start = self.location_from(self.offset(), self.column);
+                    return self.string_too_long(
+                        character_start,
+                        offset_start,
+                        column_start,
+                        line_start,
+                    );
+                }
+                Some(b'\\') => {
+                    let escape_start = self.location_from(self.offset(), self.column);
+                    let decoded = decoded.get_or_insert_with(String::new);
+                    decoded.push_str(&self.source.text(chunk_start));
+                    if self.is_json5() && self.skip_line_continuation() {
+                        chunk_start = self.offset();
+                        continue;
+                    }
+                    let character = match self.read_escape_sequence() {
+                        Ok(character) => character,
+                        Err(error) => {
+                            // Include the character the sequence ended on, unless
+                            // it was clearly not meant to be part of it.
+                            let is_included = match error {
+                                StringError::InvalidEscape => self
+                                    .current()
+                                    .is_some_and(|byte| byte.is_ascii_alphanumeric()),
+                                _ => true,
+                            };
+                            if is_included {
+                                self.read_byte();
+                            }
+                            let location = Location {
+                                length: self.column - escape_start.column,
+                                ..escape_start
+                            };
+                            return self.invalid_string(
+                                error,
+                                location,
+                                quote,
+                                offset_start,
+                                column_start,
+                                line_start,
+                            );
+                        }
+                    };
+                    decoded.push(character);
+                    if decoded.len() > self.options.max_string_length {
+                        return self.string_too_long(
+                            escape_start,
+                            offset_start,
+                            column_start,
+                            line_start,
+                        );
+                    }
+                    self.read_byte();
+                    chunk_start = self.offset();
+                }
+                Some(_) => self.read_char(),
+                None => return invalid(self),
+            }
+        }
+        let chunk = self.source.text(chunk_start);
+        let string_value = match decoded {
+            Some(mut decoded) => {
+                decoded.push_str(&chunk);
+                Cow::Owned(decoded)
+            }
+            None => chunk,
+        };
+        self.read_byte();
+        self.multiline_token_from(
+            TokenType::String(string_value),
+            offset_start,
+            column_start,
+            line_start,
+        )
+    }
+
+    /// Skips the rest of a string after `error`, up to the closing quote or
+    /// the end of the line, so that tokenizing resumes behind it.
+    fn invalid_string(
+        &mut self,
+        error: StringError,
+        location: Location,
+        quote: u8,
+        offset_start: usize,
+        column_start: usize,
+        line_start: usize,
+    ) -> Token<'a> {
+        loop {
+            match self.current() {
+                Some(byte) if byte == quote => {
+                    self.read_byte();
+                    break;
+                }
+                None | Some(b'\n' | b'\r') => break,
+                Some(b'\\') => {
+                    self.read_byte();
+                    if !matches!(self.current(), None | Some(b'\n' | b'\r')) {
+                        self.read_char();
+                    }
+                }
+                Some(_) => self.read_char(),
+            }
+        }
+        self.multiline_token_from(
+            TokenType::InvalidString(error, location),
+            offset_start,
+            column_start,
+            line_start,
+        )
+    }
+
+    fn string_too_long(
+        &self,
+        character_start: Location,
+        offset_start: usize,
+        column_start: usize,
+        line_start: usize,
+    ) -> Token<'a> {
+        let location = Location {
+            length: 1,
+            ..character_start
         };
+        self.multiline_token_from(
+            TokenType::StringTooLong(location),
+            offset_start,
+            column_start,
+            line_start,
+        )
+    }
+
+    fn read_plain_token(&mut self, token_type: TokenType<'a>) -> Token<'a> {
+        let (offset_start, column_start) = (self.offset(), self.column);
         self.read_char();
-        token
+        self.token_from(token_type, offset_start, column_start)
     }
 
-    pub fn next_token(&mut self) -> Option<Token> {
-        self.skip_whitespace();
+    pub fn next_token(&mut self) -> Option<Token<'a>> {
+        if let Some(unterminated_comment) = self.skip_whitespace() {
+            return Some(unterminated_comment);
+        }
 
-        let result = match self.character {
-            EOF => return None,
-            '[' => self.read_plain_token(TokenType::BeginArray),
-            ']' => self.read_plain_token(TokenType::EndArray),
-            '{' => self.read_plain_token(TokenType::BeginObject),
-            '}' => self.read_plain_token(TokenType::EndObject),
-            ':' => self.read_plain_token(TokenType::NameSeparator),
-            ',' => self.read_plain_token(TokenType::ValueSeparator),
-            '"' => self.read_string(),
-            _ => {
-                if is_letter(self.character) {
+        let result = match self.current()? {
+            b'[' => self.read_plain_token(TokenType::BeginArray),
+            b']' => self.read_plain_token(TokenType::EndArray),
+            b'{' => self.read_plain_token(TokenType::BeginObject),
+            b'}' => self.read_plain_token(TokenType::EndObject),
+            b':' => self.read_plain_token(TokenType::NameSeparator),
+            b',' => self.read_plain_token(TokenType::ValueSeparator),
+            b'"' => self.read_string(b'"'),
+            b'\'' if self.is_json5() => self.read_string(b'\''),
+            byte => {
+                if self.is_json5()
+                    && (is_letter(byte) || matches!(byte, b'$' | b'_' | b'\\' | 0x80..))
+                {
+                    self.read_identifier()
+                } else if is_letter(byte) {
                     self.read_literal()
-                } else if is_number_character(self.character) {
+                } else if is_number_character(byte) {
                     self.read_number()
                 } else {
                     self.read_plain_token(TokenType::Invalid)
@@ -272,42 +920,109 @@ impl Tokenizer {
 
         Some(result)
     }
+
+    /// Location right behind the last consumed character, used to report an
+    /// unexpected end of input.
+    pub fn end_location(&self) -> Location {
+        Location {
+            line: self.line,
+            column: self.column,
+            length: 1,
+            offset: self.offset(),
+        }
+    }
 }
 
-impl Iterator for Tokenizer {
-    type Item = Token;
+impl<'a, S: Source<'a>> Iterator for Tokenizer<'a, S> {
+    type Item = Token<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        return self.next_token();
+        self.next_token()
     }
 }
 
-fn is_letter(character: char) -> bool {
-    character >= 'a' && character <= 'z' || character >= 'A' && character <= 'Z'
+fn is_continuation_byte(byte: u8) -> bool {
+    byte & 0b1100_0000 == 0b1000_0000
 }
 
-fn is_number_character(character: char) -> bool {
-    character >= '0' && character <= '9'
-        || character == '-'
-        || character == '+'
-        || character == '.'
-        || character == 'e'
-        || character == 'E'
+/// Number of bytes of the UTF-8 sequence starting with `byte`.
+fn utf8_width(byte: u8) -> usize {
+    match byte {
+        0xF0.. => 4,
+        0xE0.. => 3,
+        0xC0.. => 2,
+        _ => 1,
+    }
 }
 
-fn is_hex_character(character: char) -> bool {
-    character >= '0' && character <= '9'
-        || character >= 'a' && character <= 'f'
-        || character >= 'A' && character <= 'F'
+pub(crate) fn is_letter(byte: u8) -> bool {
+    byte.is_ascii_alphabetic()
+}
+
+pub(crate) fn is_number_character(byte: u8) -> bool {
+    byte.is_ascii_digit() || matches!(byte, b'-' | b'+' | b'.' | b'e' | b'E')
+}
+
+/// Whitespace of JSON5, which adds Unicode spaces, line separators and the
+/// byte order mark to the ASCII whitespace of JSON.
+fn is_json5_whitespace(character: char) -> bool {
+    (character.is_whitespace() && character != '\u{85}') || character == '\u{feff}'
+}
+
+fn is_line_separator(character: char) -> bool {
+    matches!(character, '\u{2028}' | '\u{2029}')
+}
+
+/// Approximates the ECMAScript `IdentifierStart` and `IdentifierPart` classes.
+fn is_identifier_character(character: char, is_start: bool) -> bool {
+    character.is_alphabetic()
+        || matches!(character, '$' | '_')
+        || (!is_start
+            && (character.is_alphanumeric() || matches!(character, '\u{200c}' | '\u{200d}')))
+}
+
+/// Value of a JSON5 hexadecimal integer such as `-0x1F`, which becomes a float
+/// once it exceeds the integer types.
+fn hex_number(lexeme: &str) -> Number {
+    let negative = lexeme.starts_with('-');
+    let digits = &lexeme[lexeme.find(['x', 'X']).expect("hex numbers contain x") + 1..];
+    match u64::from_str_radix(digits, 16) {
+        Ok(value) if !negative => Number::UnsingedInteger(value),
+        Ok(value) if value <= i64::MIN.unsigned_abs() => {
+            Number::Integer((value as i64).wrapping_neg())
+        }
+        _ => {
+            let value = digits.bytes().fold(0.0, |value, digit| {
+                value * 16.0 + (digit as char).to_digit(16).unwrap_or(0) as f64
+            });
+            Number::Float(if negative { -value } else { value })
+        }
+    }
+}
+
+/// Rewrites a JSON5 decimal number in the form JSON requires, e.g. `+.5` as
+/// `0.5` and `5.e3` as `5e3`.
+fn to_json_number(lexeme: &str) -> String {
+    let lexeme = lexeme.strip_prefix('+').unwrap_or(lexeme);
+    let (sign, lexeme) = match lexeme.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", lexeme),
+    };
+    let (mantissa, exponent) = lexeme.split_at(lexeme.find(['e', 'E']).unwrap_or(lexeme.len()));
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let integer = if integer.is_empty() { "0" } else { integer };
+    let point = if fraction.is_empty() { "" } else { "." };
+    format!("{}{}{}{}{}", sign, integer, point, fraction, exponent)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parser::{test_readers::ByteReader, Dialect};
     use test_case::test_case;
 
-    fn collect_tokens(json: &str) -> Vec<Token> {
-        let lexer = Tokenizer::new(String::from(json));
+    fn collect_tokens(json: &str) -> Vec<Token<'_>> {
+        let lexer = Tokenizer::new(json);
         let tokens: Vec<Token> = lexer.collect();
         tokens
     }
@@ -322,6 +1037,8 @@ mod tests {
     #[test_case("0.534", Number::Float(0.534) ; "Float: 0 < x < 1")]
     #[test_case("234.534", Number::Float(234.534) ; "Float: x > 1")]
     #[test_case("0.22e-2", Number::Float(0.0022) ; "Float with exponent notation")]
+    #[test_case("0", Number::UnsingedInteger(0) ; "Zero")]
+    #[test_case("-0.5e+3", Number::Integer(-500) ; "Negative fraction with signed exponent")]
     fn number_tokens(json: &str, expected_number: Number) {
         let tokens = collect_tokens(json);
         assert_eq!(TokenType::Number(expected_number), tokens[0].token_type)
@@ -344,7 +1061,7 @@ mod tests {
         let tokens = collect_tokens(json);
         assert_eq!(
             tokens[0].token_type,
-            TokenType::String(String::from(expected_string))
+            TokenType::String(Cow::from(expected_string))
         );
     }
 
@@ -354,11 +1071,12 @@ mod tests {
         assert_eq!(
             Token {
                 token_type: TokenType::BeginArray,
-                value: String::from("["),
+                value: Cow::from("["),
                 location: Location {
                     line: 1,
                     column: 1,
                     length: 1,
+                    offset: 0,
                 }
             },
             tokens[0]
@@ -366,11 +1084,12 @@ mod tests {
         assert_eq!(
             Token {
                 token_type: TokenType::Number(Number::UnsingedInteger(5)),
-                value: String::from("5"),
+                value: Cow::from("5"),
                 location: Location {
                     line: 1,
                     column: 2,
                     length: 1,
+                    offset: 1,
                 }
             },
             tokens[1]
@@ -378,11 +1097,12 @@ mod tests {
         assert_eq!(
             Token {
                 token_type: TokenType::EndArray,
-                value: String::from("]"),
+                value: Cow::from("]"),
                 location: Location {
                     line: 1,
                     column: 3,
                     length: 1,
+                    offset: 2,
                 }
             },
             tokens[2]
@@ -395,23 +1115,25 @@ mod tests {
         assert_eq!(
             Token {
                 token_type: TokenType::BeginObject,
-                value: String::from("{"),
+                value: Cow::from("{"),
                 location: Location {
                     line: 1,
                     column: 1,
                     length: 1,
+                    offset: 0,
                 }
             },
             tokens[0]
         );
         assert_eq!(
             Token {
-                token_type: TokenType::String(String::from("key")),
-                value: String::from("\"key\""),
+                token_type: TokenType::String(Cow::from("key")),
+                value: Cow::from("\"key\""),
                 location: Location {
                     line: 1,
                     column: 3,
                     length: 5,
+                    offset: 2,
                 }
             },
             tokens[1]
@@ -419,23 +1141,25 @@ mod tests {
         assert_eq!(
             Token {
                 token_type: TokenType::NameSeparator,
-                value: String::from(":"),
+                value: Cow::from(":"),
                 location: Location {
                     line: 1,
                     column: 8,
                     length: 1,
+                    offset: 7,
                 }
             },
             tokens[2]
         );
         assert_eq!(
             Token {
-                token_type: TokenType::String(String::from("value")),
-                value: String::from("\"value\""),
+                token_type: TokenType::String(Cow::from("value")),
+                value: Cow::from("\"value\""),
                 location: Location {
                     line: 1,
                     column: 10,
                     length: 7,
+                    offset: 9,
                 }
             },
             tokens[3]
@@ -443,14 +1167,95 @@ mod tests {
         assert_eq!(
             Token {
                 token_type: TokenType::EndObject,
-                value: String::from("}"),
+                value: Cow::from("}"),
                 location: Location {
                     line: 1,
                     column: 18,
                     length: 1,
+                    offset: 17,
                 }
             },
             tokens[4]
         )
     }
+
+    #[test_case("[\"ü\", 1]", 3, (1, 7, 7) ; "Multi-byte character in string")]
+    #[test_case("\"𝄞\"\n 1", 1, (2, 2, 8) ; "Four byte character before newline")]
+    #[test_case("[\r\n 1]", 1, (2, 2, 4) ; "Carriage return and line feed count as one line")]
+    fn token_positions(json: &str, token_idx: usize, expected: (usize, usize, usize)) {
+        let location = collect_tokens(json)[token_idx].location;
+        assert_eq!((location.line, location.column, location.offset), expected);
+    }
+
+    #[test_case("// ü\n[1]", 0, (2, 1, 6) ; "Line comment")]
+    #[test_case("[/* a\n bc */ 1]", 1, (2, 8, 13) ; "Block comment spanning lines")]
+    #[test_case("/* 𝄞 */ 1 // x", 0, (1, 9, 11) ; "Multi-byte character in comment")]
+    #[test_case("[1 /** / **/]", 2, (1, 13, 12) ; "Stars and slashes in block comment")]
+    fn comment_positions(json: &str, token_idx: usize, expected: (usize, usize, usize)) {
+        let options = ParseOptions::new().dialect(Dialect::Jsonc);
+        let tokens: Vec<Token> = Tokenizer::with_options(json, options).collect();
+        let location = tokens[token_idx].location;
+        assert_eq!((location.line, location.column, location.offset), expected);
+    }
+
+    #[test]
+    fn unterminated_comment() {
+        let options = ParseOptions::new().dialect(Dialect::Jsonc);
+        let tokens: Vec<Token> = Tokenizer::with_options("[1,\n  /* open\n", options).collect();
+        let last = tokens.last().unwrap();
+        assert_eq!(last.token_type, TokenType::UnterminatedComment);
+        assert_eq!((last.location.line, last.location.column), (2, 3));
+    }
+
+    #[test]
+    fn comments_in_json() {
+        let token_types: Vec<TokenType> = collect_tokens("// x")
+            .into_iter()
+            .map(|token| token.token_type)
+            .collect();
+        assert_eq!(token_types[0], TokenType::Invalid);
+    }
+
+    #[test]
+    fn json5_positions() {
+        let json = "['a\\\n bc', \u{a0}x: 0x1F\u{2028}1]";
+        let options = ParseOptions::new().dialect(Dialect::Json5);
+        let tokens: Vec<Token> = Tokenizer::with_options(json, options).collect();
+        let positions: Vec<(usize, usize, usize, usize)> = tokens
+            .iter()
+            .map(|token| {
+                let location = token.location;
+                (
+                    location.line,
+                    location.column,
+                    location.length,
+                    location.offset,
+                )
+            })
+            .collect();
+        assert_eq!(
+            positions,
+            vec![
+                (1, 1, 1, 0),
+                (1, 2, 8, 1),
+                (2, 5, 1, 9),
+                (2, 8, 1, 13),
+                (2, 9, 1, 14),
+                (2, 11, 4, 16),
+                (3, 1, 1, 23),
+                (3, 2, 1, 24),
+            ]
+        );
+        assert_eq!(tokens[1].token_type, TokenType::String(Cow::from("a bc")));
+        assert_eq!(tokens[3].token_type, TokenType::Identifier(Cow::from("x")));
+    }
+
+    #[test_case(r#"{ "key": [1.5e3, true, null] }"# ; "Mixed tokens")]
+    #[test_case("[\"grüße\\n\",\r\n \"\\uD834\\uDD1E\"]" ; "Escapes and line breaks")]
+    #[test_case("[01, tru, \"open" ; "Invalid tokens")]
+    fn reader_tokens(json: &str) {
+        let from_reader: Vec<Token> =
+            Tokenizer::from_reader(ByteReader(json.as_bytes()), ParseOptions::default()).collect();
+        assert_eq!(from_reader, collect_tokens(json));
+    }
 }
@@ -1,7 +1,8 @@
 use super::{super::stringifier::JsonStringifier, number::Number};
 use indexmap::map::IndexMap;
+use std::borrow::Cow;
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum Value {
     Null,
     Bool(bool),
@@ -12,7 +13,39 @@ pub enum Value {
 }
 
 impl Value {
-    pub fn stringified(&self) -> JsonStringifier {
+    pub fn stringified(&self) -> JsonStringifier<'_> {
         JsonStringifier::new(self)
     }
 }
+
+/// A [`Value`] whose strings and object keys borrow from the parsed input.
+/// Only strings containing escape sequences are allocated.
+#[derive(Debug, PartialEq)]
+pub enum BorrowedValue<'a> {
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(Cow<'a, str>),
+    Object(IndexMap<Cow<'a, str>, BorrowedValue<'a>>),
+    Array(Vec<BorrowedValue<'a>>),
+}
+
+impl BorrowedValue<'_> {
+    pub fn into_owned(self) -> Value {
+        match self {
+            Self::Null => Value::Null,
+            Self::Bool(bool) => Value::Bool(bool),
+            Self::Number(number) => Value::Number(number),
+            Self::String(string) => Value::String(string.into_owned()),
+            Self::Object(object) => Value::Object(
+                object
+                    .into_iter()
+                    .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                    .collect(),
+            ),
+            Self::Array(array) => {
+                Value::Array(array.into_iter().map(BorrowedValue::into_owned).collect())
+            }
+        }
+    }
+}
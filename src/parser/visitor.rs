@@ -0,0 +1,288 @@
+use super::{
+    events::Scalar,
+    number::Number,
+    options::DuplicateKeyPolicy,
+    spanned::Span,
+    value::{BorrowedValue, Value},
+};
+use indexmap::{map::Entry, IndexMap};
+use std::{borrow::Cow, collections::HashSet, convert::Infallible, error::Error, hash::Hash, mem};
+
+/// Receives the contents of a document in order while it is parsed, so it
+/// can be turned into any structure without building a [`Value`] first.
+///
+/// Objects arrive as `begin_object`, then `visit_key` followed by the value
+/// for every property and finally `end_object`. Arrays work alike without
+/// keys. Every key and value, once complete, is followed by `visit_span`.
+///
+/// A callback returning an error stops parsing right away. The error is
+/// reported as [`ParseError::Rejected`](super::ParseError::Rejected) at the
+/// location of the token being visited.
+pub trait Visitor<'a> {
+    /// Why a visitor rejects the document, [`Infallible`] if it never does.
+    type Error: Into<Box<dyn Error + Send + Sync>>;
+
+    fn visit_null(&mut self) -> Result<(), Self::Error>;
+    fn visit_bool(&mut self, bool: bool) -> Result<(), Self::Error>;
+    fn visit_number(&mut self, number: Number) -> Result<(), Self::Error>;
+    fn visit_string(&mut self, string: Cow<'a, str>) -> Result<(), Self::Error>;
+    fn begin_object(&mut self) -> Result<(), Self::Error>;
+    fn visit_key(&mut self, key: Cow<'a, str>) -> Result<(), Self::Error>;
+    fn end_object(&mut self) -> Result<(), Self::Error>;
+    fn begin_array(&mut self) -> Result<(), Self::Error>;
+    fn end_array(&mut self) -> Result<(), Self::Error>;
+
+    /// Where the key or value visited last, including nested values, was
+    /// found in the input. Ignored unless overridden.
+    fn visit_span(&mut self, _span: Span) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Value trees the [`TreeBuilder`] can assemble.
+pub trait Tree<'a>: Sized {
+    type Key: Hash + Eq;
+
+    fn scalar(scalar: Scalar<'a>) -> Self;
+    fn key(key: Cow<'a, str>) -> Self::Key;
+    fn object(object: IndexMap<Self::Key, Self>) -> Self;
+    fn array(array: Vec<Self>) -> Self;
+    fn as_array_mut(&mut self) -> Option<&mut Vec<Self>>;
+}
+
+impl<'a> Tree<'a> for Value {
+    type Key = String;
+
+    fn scalar(scalar: Scalar<'a>) -> Self {
+        match scalar {
+            Scalar::Null => Value::Null,
+            Scalar::Bool(bool) => Value::Bool(bool),
+            Scalar::Number(number) => Value::Number(number),
+            Scalar::String(string) => Value::String(string.into_owned()),
+        }
+    }
+
+    fn key(key: Cow<'a, str>) -> Self::Key {
+        key.into_owned()
+    }
+
+    fn object(object: IndexMap<Self::Key, Self>) -> Self {
+        Value::Object(object)
+    }
+
+    fn array(array: Vec<Self>) -> Self {
+        Value::Array(array)
+    }
+
+    fn as_array_mut(&mut self) -> Option<&mut Vec<Self>> {
+        match self {
+            Value::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Tree<'a> for BorrowedValue<'a> {
+    type Key = Cow<'a, str>;
+
+    fn scalar(scalar: Scalar<'a>) -> Self {
+        match scalar {
+            Scalar::Null => BorrowedValue::Null,
+            Scalar::Bool(bool) => BorrowedValue::Bool(bool),
+            Scalar::Number(number) => BorrowedValue::Number(number),
+            Scalar::String(string) => BorrowedValue::String(string),
+        }
+    }
+
+    fn key(key: Cow<'a, str>) -> Self::Key {
+        key
+    }
+
+    fn object(object: IndexMap<Self::Key, Self>) -> Self {
+        BorrowedValue::Object(object)
+    }
+
+    fn array(array: Vec<Self>) -> Self {
+        BorrowedValue::Array(array)
+    }
+
+    fn as_array_mut(&mut self) -> Option<&mut Vec<Self>> {
+        match self {
+            BorrowedValue::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+}
+
+enum Frame<'a, T: Tree<'a>> {
+    Object {
+        object: IndexMap<T::Key, T>,
+        key: Option<T::Key>,
+        /// Indices of members whose values were collected into an array.
+        collected: HashSet<usize>,
+    },
+    Array(Vec<T>),
+}
+
+/// The built-in visitor assembling a [`Value`] or [`BorrowedValue`].
+pub struct TreeBuilder<'a, T: Tree<'a>> {
+    stack: Vec<Frame<'a, T>>,
+    result: Option<T>,
+    duplicate_keys: DuplicateKeyPolicy,
+}
+
+impl<'a, T: Tree<'a>> Default for TreeBuilder<'a, T> {
+    fn default() -> Self {
+        Self {
+            stack: Vec::new(),
+            result: None,
+            duplicate_keys: DuplicateKeyPolicy::default(),
+        }
+    }
+}
+
+impl<'a, T: Tree<'a>> TreeBuilder<'a, T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder resolving repeated keys according to `duplicate_keys`.
+    /// [`DuplicateKeyPolicy::Error`] is left to the parser and behaves like
+    /// [`DuplicateKeyPolicy::LastWins`] here.
+    pub fn with_duplicate_keys(duplicate_keys: DuplicateKeyPolicy) -> Self {
+        Self {
+            duplicate_keys,
+            ..Self::default()
+        }
+    }
+
+    /// The completed top-level value, if one was visited entirely.
+    pub fn finish(self) -> Option<T> {
+        self.result
+    }
+
+    fn push(&mut self, value: T) {
+        match self.stack.last_mut() {
+            Some(Frame::Object {
+                object,
+                key,
+                collected,
+            }) => {
+                if let Some(key) = key.take() {
+                    insert(object, collected, key, value, self.duplicate_keys);
+                }
+            }
+            Some(Frame::Array(array)) => array.push(value),
+            None => self.result = Some(value),
+        }
+    }
+}
+
+/// Object members that [`DuplicateKeyPolicy::Collect`] can gather into an
+/// array.
+pub trait Collect: Sized {
+    /// Replaces `self` with an array of `self` and `value`.
+    fn collect_first(&mut self, value: Self);
+    /// Appends `value` to the array made by [`collect_first`](Self::collect_first).
+    fn collect(&mut self, value: Self);
+}
+
+impl<'a, T: Tree<'a>> Collect for T {
+    fn collect_first(&mut self, value: Self) {
+        let first = mem::replace(self, T::array(Vec::new()));
+        *self = T::array(vec![first, value]);
+    }
+
+    fn collect(&mut self, value: Self) {
+        if let Some(array) = self.as_array_mut() {
+            array.push(value);
+        }
+    }
+}
+
+/// Adds `value` to `object`, resolving a repeated `key` according to
+/// `duplicate_keys`. `collected` holds the indices of members whose values
+/// were collected into an array.
+pub fn insert<K: Hash + Eq, V: Collect>(
+    object: &mut IndexMap<K, V>,
+    collected: &mut HashSet<usize>,
+    key: K,
+    value: V,
+    duplicate_keys: DuplicateKeyPolicy,
+) {
+    match (object.entry(key), duplicate_keys) {
+        (Entry::Vacant(entry), _) => {
+            entry.insert(value);
+        }
+        (Entry::Occupied(_), DuplicateKeyPolicy::FirstWins) => {}
+        (Entry::Occupied(mut entry), DuplicateKeyPolicy::Collect) => {
+            if collected.insert(entry.index()) {
+                entry.get_mut().collect_first(value);
+            } else {
+                entry.get_mut().collect(value);
+            }
+        }
+        (Entry::Occupied(mut entry), _) => {
+            entry.insert(value);
+        }
+    }
+}
+
+impl<'a, T: Tree<'a>> Visitor<'a> for TreeBuilder<'a, T> {
+    type Error = Infallible;
+
+    fn visit_null(&mut self) -> Result<(), Self::Error> {
+        self.push(T::scalar(Scalar::Null));
+        Ok(())
+    }
+
+    fn visit_bool(&mut self, bool: bool) -> Result<(), Self::Error> {
+        self.push(T::scalar(Scalar::Bool(bool)));
+        Ok(())
+    }
+
+    fn visit_number(&mut self, number: Number) -> Result<(), Self::Error> {
+        self.push(T::scalar(Scalar::Number(number)));
+        Ok(())
+    }
+
+    fn visit_string(&mut self, string: Cow<'a, str>) -> Result<(), Self::Error> {
+        self.push(T::scalar(Scalar::String(string)));
+        Ok(())
+    }
+
+    fn begin_object(&mut self) -> Result<(), Self::Error> {
+        self.stack.push(Frame::Object {
+            object: IndexMap::new(),
+            key: None,
+            collected: HashSet::new(),
+        });
+        Ok(())
+    }
+
+    fn visit_key(&mut self, key: Cow<'a, str>) -> Result<(), Self::Error> {
+        if let Some(Frame::Object { key: pending, .. }) = self.stack.last_mut() {
+            *pending = Some(T::key(key));
+        }
+        Ok(())
+    }
+
+    fn end_object(&mut self) -> Result<(), Self::Error> {
+        if let Some(Frame::Object { object, .. }) = self.stack.pop() {
+            self.push(T::object(object));
+        }
+        Ok(())
+    }
+
+    fn begin_array(&mut self) -> Result<(), Self::Error> {
+        self.stack.push(Frame::Array(Vec::new()));
+        Ok(())
+    }
+
+    fn end_array(&mut self) -> Result<(), Self::Error> {
+        if let Some(Frame::Array(array)) = self.stack.pop() {
+            self.push(T::array(array));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,471 @@
+use super::{number::Number, value::Value};
+use std::{
+    fmt::{self, Display},
+    io::{self, Write},
+};
+
+/// Writes JSON straight to an [`io::Write`], one event at a time, so that
+/// huge documents can be produced without building a [`Value`] or the
+/// whole output in memory first. Every event is checked against what came
+/// before it, so the output is always well-formed JSON:
+///
+/// ```
+/// use json_parser::parser::JsonWriter;
+///
+/// let mut writer = JsonWriter::new(Vec::new()).compact(true);
+/// writer.begin_object()?;
+/// writer.key("values")?;
+/// writer.begin_array()?;
+/// writer.bool(true)?;
+/// writer.null()?;
+/// writer.end_array()?;
+/// writer.end_object()?;
+/// assert_eq!(writer.finish()?, br#"{"values":[true,null]}"#);
+/// # Ok::<(), json_parser::parser::WriteError>(())
+/// ```
+///
+/// Formatting follows [`Value::stringified`]. Output is passed on in small
+/// pieces, so wrap unbuffered writers such as files in an
+/// [`io::BufWriter`]. After an I/O error the output is incomplete and the
+/// writer should not be used any further.
+pub struct JsonWriter<W: Write> {
+    writer: W,
+    indent: u8,
+    compact: bool,
+    json5: bool,
+    /// Open objects and arrays, innermost last.
+    stack: Vec<Container>,
+    /// Whether the top-level value has been started.
+    has_value: bool,
+}
+
+struct Container {
+    is_object: bool,
+    is_empty: bool,
+    /// Whether an object has a key waiting for its value.
+    has_key: bool,
+}
+
+/// Misuse of a [`JsonWriter`] that would have produced malformed JSON, or
+/// an error of the underlying writer.
+#[derive(Debug)]
+pub enum WriteError {
+    Io(io::Error),
+    /// A key outside of an object, or right after another key.
+    UnexpectedKey,
+    /// A value where an object expects a key, or after the top-level value.
+    UnexpectedValue,
+    /// Closing an object or array that is not the innermost open one, or an
+    /// object whose last key lacks a value.
+    UnexpectedEnd,
+    /// Finishing before the top-level value is complete.
+    Incomplete,
+}
+
+impl Display for WriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{}", error),
+            Self::UnexpectedKey => write!(f, "Key not expected here"),
+            Self::UnexpectedValue => write!(f, "Value not expected here"),
+            Self::UnexpectedEnd => write!(f, "No matching object or array to end"),
+            Self::Incomplete => write!(f, "Document is incomplete"),
+        }
+    }
+}
+
+impl std::error::Error for WriteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for WriteError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl<W: Write> JsonWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            indent: 2,
+            compact: false,
+            json5: false,
+            stack: Vec::new(),
+            has_value: false,
+        }
+    }
+
+    pub fn indent(mut self, indent: u8) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Write everything on one line without any whitespace.
+    pub fn compact(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+
+    /// Write JSON5: keys that are identifiers go unquoted, strings containing
+    /// double quotes use single quotes, members end with a trailing comma and
+    /// non-finite numbers are written as `Infinity` or `NaN` instead of `null`.
+    pub fn json5(mut self, json5: bool) -> Self {
+        self.json5 = json5;
+        self
+    }
+
+    pub fn begin_object(&mut self) -> Result<(), WriteError> {
+        self.begin_value()?;
+        Ok(self.open(true)?)
+    }
+
+    pub fn end_object(&mut self) -> Result<(), WriteError> {
+        match self.stack.last() {
+            Some(container) if container.is_object && !container.has_key => Ok(self.close()?),
+            _ => Err(WriteError::UnexpectedEnd),
+        }
+    }
+
+    pub fn begin_array(&mut self) -> Result<(), WriteError> {
+        self.begin_value()?;
+        Ok(self.open(false)?)
+    }
+
+    pub fn end_array(&mut self) -> Result<(), WriteError> {
+        match self.stack.last() {
+            Some(container) if !container.is_object => Ok(self.close()?),
+            _ => Err(WriteError::UnexpectedEnd),
+        }
+    }
+
+    /// Writes the key of the next object member, whose value has to follow.
+    pub fn key(&mut self, key: &str) -> Result<(), WriteError> {
+        match self.stack.last() {
+            Some(container) if container.is_object && !container.has_key => {}
+            _ => return Err(WriteError::UnexpectedKey),
+        }
+        self.member_key(key)?;
+        if let Some(container) = self.stack.last_mut() {
+            container.has_key = true;
+        }
+        Ok(())
+    }
+
+    pub fn null(&mut self) -> Result<(), WriteError> {
+        self.begin_value()?;
+        Ok(self.writer.write_all(b"null")?)
+    }
+
+    pub fn bool(&mut self, bool: bool) -> Result<(), WriteError> {
+        self.begin_value()?;
+        Ok(write!(self.writer, "{}", bool)?)
+    }
+
+    pub fn number(&mut self, number: &Number) -> Result<(), WriteError> {
+        self.begin_value()?;
+        Ok(self.write_number(number)?)
+    }
+
+    pub fn string(&mut self, string: &str) -> Result<(), WriteError> {
+        self.begin_value()?;
+        Ok(self.write_string(string)?)
+    }
+
+    /// Writes a whole value, e.g. a part of the document that is already at
+    /// hand as a [`Value`].
+    pub fn value(&mut self, value: &Value) -> Result<(), WriteError> {
+        self.begin_value()?;
+        Ok(self.tree(value)?)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Flushes and returns the underlying writer once the top-level value is
+    /// complete.
+    pub fn finish(mut self) -> Result<W, WriteError> {
+        if !self.has_value || !self.stack.is_empty() {
+            return Err(WriteError::Incomplete);
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    /// Returns the underlying writer without checking that the document is
+    /// complete.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Checks that a value may follow and writes what has to precede it.
+    fn begin_value(&mut self) -> Result<(), WriteError> {
+        match self.stack.last_mut() {
+            None if self.has_value => return Err(WriteError::UnexpectedValue),
+            None => self.has_value = true,
+            Some(container) if container.is_object => {
+                if !container.has_key {
+                    return Err(WriteError::UnexpectedValue);
+                }
+                container.has_key = false;
+            }
+            Some(_) => self.member()?,
+        }
+        Ok(())
+    }
+
+    fn tree(&mut self, value: &Value) -> io::Result<()> {
+        match value {
+            Value::Null => self.writer.write_all(b"null"),
+            Value::Bool(bool) => write!(self.writer, "{}", bool),
+            Value::Number(number) => self.write_number(number),
+            Value::String(string) => self.write_string(string),
+            Value::Object(object) => {
+                self.open(true)?;
+                for (key, value) in object {
+                    self.member_key(key)?;
+                    self.tree(value)?;
+                }
+                self.close()
+            }
+            Value::Array(array) => {
+                self.open(false)?;
+                for value in array {
+                    self.member()?;
+                    self.tree(value)?;
+                }
+                self.close()
+            }
+        }
+    }
+
+    fn open(&mut self, is_object: bool) -> io::Result<()> {
+        self.writer.write_all(if is_object { b"{" } else { b"[" })?;
+        self.stack.push(Container {
+            is_object,
+            is_empty: true,
+            has_key: false,
+        });
+        Ok(())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        let container = self.stack.pop().expect("a container is open");
+        if !container.is_empty {
+            if self.json5 {
+                self.writer.write_all(b",")?;
+            }
+            self.line_break()?;
+        }
+        self.writer
+            .write_all(if container.is_object { b"}" } else { b"]" })
+    }
+
+    /// Starts the next member of the innermost container on a new line,
+    /// separated from the previous one.
+    fn member(&mut self) -> io::Result<()> {
+        let container = self.stack.last_mut().expect("a container is open");
+        if !std::mem::replace(&mut container.is_empty, false) {
+            self.writer.write_all(b",")?;
+        }
+        self.line_break()
+    }
+
+    fn member_key(&mut self, key: &str) -> io::Result<()> {
+        self.member()?;
+        if self.json5 && is_identifier(key) {
+            self.writer.write_all(key.as_bytes())?;
+        } else {
+            self.write_string(key)?;
+        }
+        self.writer
+            .write_all(if self.compact { b":" } else { b": " })
+    }
+
+    /// Starts a new line indented to the depth of the innermost container,
+    /// unless compact.
+    fn line_break(&mut self) -> io::Result<()> {
+        if self.compact {
+            return Ok(());
+        }
+        self.writer.write_all(b"\n")?;
+        let spaces = self.stack.len() * usize::from(self.indent);
+        write!(self.writer, "{:spaces$}", "")
+    }
+
+    fn write_number(&mut self, number: &Number) -> io::Result<()> {
+        match number {
+            Number::Float(float) if !float.is_finite() && !self.json5 => {
+                self.writer.write_all(b"null")
+            }
+            Number::Float(float) if float.is_nan() => self.writer.write_all(b"NaN"),
+            Number::Float(float) if float.is_infinite() && *float < 0.0 => {
+                self.writer.write_all(b"-Infinity")
+            }
+            Number::Float(float) if float.is_infinite() => self.writer.write_all(b"Infinity"),
+            number => write!(self.writer, "{}", number),
+        }
+    }
+
+    fn write_string(&mut self, string: &str) -> io::Result<()> {
+        let quote = if self.json5 && string.contains('"') && !string.contains('\'') {
+            '\''
+        } else {
+            '"'
+        };
+        write!(self.writer, "{}", quote)?;
+        // Characters that need no escaping are passed on in runs.
+        let mut start = 0;
+        for (index, character) in string.char_indices() {
+            if character != quote && character != '\\' && !character.is_control() {
+                continue;
+            }
+            self.writer.write_all(&string.as_bytes()[start..index])?;
+            start = index + character.len_utf8();
+            match character {
+                '\u{8}' => self.writer.write_all(br"\b")?,
+                '\u{c}' => self.writer.write_all(br"\f")?,
+                '\n' => self.writer.write_all(br"\n")?,
+                '\r' => self.writer.write_all(br"\r")?,
+                '\t' => self.writer.write_all(br"\t")?,
+                '\u{0}'..='\u{1f}' => write!(self.writer, "\\u{:04x}", u32::from(character))?,
+                '"' | '\'' | '\\' => write!(self.writer, "\\{}", character)?,
+                // Other control characters are allowed in strings as they are.
+                _ => self.writer.write_all(&string.as_bytes()[index..start])?,
+            }
+        }
+        self.writer.write_all(&string.as_bytes()[start..])?;
+        write!(self.writer, "{}", quote)
+    }
+}
+
+/// Whether `key` can be written without quotes in JSON5. Only ASCII
+/// identifiers qualify to stay clear of Unicode category subtleties.
+fn is_identifier(key: &str) -> bool {
+    let mut characters = key.chars();
+    characters
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || matches!(first, '$' | '_'))
+        && characters
+            .all(|character| character.is_ascii_alphanumeric() || matches!(character, '$' | '_'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse, parse_with, Dialect};
+    use test_case::test_case;
+
+    fn output(writer: JsonWriter<Vec<u8>>) -> String {
+        String::from_utf8(writer.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn events() {
+        let mut writer = JsonWriter::new(Vec::new());
+        writer.begin_object().unwrap();
+        writer.key("name").unwrap();
+        writer.string("qj").unwrap();
+        writer.key("list").unwrap();
+        writer.begin_array().unwrap();
+        writer.number(&Number::Integer(-1)).unwrap();
+        writer.number(&Number::Float(2.5)).unwrap();
+        writer.begin_object().unwrap();
+        writer.end_object().unwrap();
+        writer.begin_array().unwrap();
+        writer.end_array().unwrap();
+        writer.end_array().unwrap();
+        writer.key("nested").unwrap();
+        writer
+            .value(&parse(r#"{"a": [true, null]}"#).unwrap())
+            .unwrap();
+        writer.end_object().unwrap();
+        assert_eq!(
+            output(writer),
+            "{\n  \"name\": \"qj\",\n  \"list\": [\n    -1,\n    2.5,\n    {},\n    []\n  ],\n  \
+             \"nested\": {\n    \"a\": [\n      true,\n      null\n    ]\n  }\n}"
+        );
+    }
+
+    #[test_case(false, r#"{"a": [1, "it's \"x\""]}"#, r#"{"a":[1,"it's \"x\""]}"# ; "Compact")]
+    #[test_case(true, r#"{"a-b": [1], "c": "say \"hi\""}"#, r#"{"a-b":[1,],c:'say "hi"',}"# ; "Compact JSON5")]
+    #[test_case(true, "[Infinity, -Infinity, NaN]", "[Infinity,-Infinity,NaN,]" ; "Non-finite numbers")]
+    #[test_case(false, "[Infinity, NaN]", "[null,null]" ; "Non-finite numbers as null")]
+    #[test_case(false, r#""\u0000\u001f\u007f\\ \b\f\n\r\t 🎉""#, "\"\\u0000\\u001f\u{7f}\\\\ \\b\\f\\n\\r\\t 🎉\"" ; "Escapes")]
+    fn formatting(json5: bool, json: &str, expected: &str) {
+        let value = parse_with(json, Dialect::Json5).unwrap();
+        let mut writer = JsonWriter::new(Vec::new()).compact(true).json5(json5);
+        writer.value(&value).unwrap();
+        assert_eq!(output(writer), expected);
+    }
+
+    #[test]
+    fn misuse() {
+        let mut writer = JsonWriter::new(Vec::new());
+        assert!(matches!(writer.key("a"), Err(WriteError::UnexpectedKey)));
+        assert!(matches!(writer.end_array(), Err(WriteError::UnexpectedEnd)));
+        writer.begin_object().unwrap();
+        assert!(matches!(writer.null(), Err(WriteError::UnexpectedValue)));
+        assert!(matches!(writer.end_array(), Err(WriteError::UnexpectedEnd)));
+        writer.key("a").unwrap();
+        assert!(matches!(writer.key("b"), Err(WriteError::UnexpectedKey)));
+        assert!(matches!(
+            writer.end_object(),
+            Err(WriteError::UnexpectedEnd)
+        ));
+        writer.begin_array().unwrap();
+        assert!(matches!(writer.key("c"), Err(WriteError::UnexpectedKey)));
+        assert!(matches!(
+            writer.end_object(),
+            Err(WriteError::UnexpectedEnd)
+        ));
+        writer.end_array().unwrap();
+        writer.end_object().unwrap();
+        assert!(matches!(
+            writer.bool(true),
+            Err(WriteError::UnexpectedValue)
+        ));
+        // Rejected events leave no trace in the output.
+        assert_eq!(output(writer), "{\n  \"a\": []\n}");
+    }
+
+    #[test_case(&[] ; "Nothing written")]
+    #[test_case(&["[", "1"] ; "Open array")]
+    #[test_case(&["{", "a"] ; "Key without value")]
+    fn incomplete(events: &[&str]) {
+        let mut writer = JsonWriter::new(Vec::new());
+        for event in events {
+            match *event {
+                "[" => writer.begin_array(),
+                "{" => writer.begin_object(),
+                "1" => writer.number(&Number::Integer(1)),
+                key => writer.key(key),
+            }
+            .unwrap();
+        }
+        assert!(matches!(writer.finish(), Err(WriteError::Incomplete)));
+    }
+
+    #[test]
+    fn io_error() {
+        struct FailingWriter;
+        impl Write for FailingWriter {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::Error::other("disk full"))
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+        let mut writer = JsonWriter::new(FailingWriter);
+        let error = writer.string("text").unwrap_err();
+        assert!(matches!(error, WriteError::Io(_)));
+        assert_eq!(error.to_string(), "disk full");
+    }
+}